@@ -1,57 +1,110 @@
-mod aim_trainer;
-mod chimp_test;
-mod number_memory;
-mod reaction_time;
-mod savestate;
-mod sequence_memory;
-mod typing_test;
-mod verbal_memory;
+pub mod aim_trainer;
+pub mod chimp_test;
+pub mod clock;
+pub mod dashboard;
+pub mod events;
+pub mod export;
+pub mod import;
+pub mod migration;
+pub mod number_memory;
+pub mod periods;
+pub mod plausibility;
+pub mod players;
+pub mod population;
+pub mod profile;
+pub mod reaction_time;
+pub mod recovery;
+pub mod regions;
+pub mod registry;
+pub mod rng;
+pub mod savestate;
+pub mod sequence_memory;
+pub mod sessions;
+pub mod stats;
+pub mod storage;
+pub mod typing_test;
+pub mod verbal_memory;
+pub mod visual_memory;
 
 use std::{
     fmt::Debug,
     fs::{self, OpenOptions},
     io::{self, Write},
+    path::{Path, PathBuf},
     time::Duration,
 };
 
 use chrono::{DateTime, Local};
-use directories::BaseDirs;
+use dashboard::Dashboard;
+use events::EventSource;
+use plausibility::Rules;
+use players::Players;
+use population::Population;
+use profile::Profile;
 use ratatui::{
-    DefaultTerminal, Frame,
+    DefaultTerminal, Frame, Terminal,
+    backend::Backend,
     crossterm::event::{self, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Styled, Stylize},
+    style::{Color, Style, Styled, Stylize},
     symbols::{Marker, border},
-    widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph, Widget},
+    text::Line,
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph, Sparkline, Tabs, Widget},
 };
+use recovery::Recovery;
+use regions::Regions;
+use savestate::{Attempt, SaveState};
+use sessions::Phase;
+use stats::{Better, Stats};
 
 pub trait Game {
-    fn run(terminal: &mut DefaultTerminal) -> io::Result<()>;
-    fn handle_input(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()>;
-    fn draw(&self, frame: &mut Frame);
+    fn run<B: Backend>(terminal: &mut Terminal<B>, events: &mut dyn EventSource) -> io::Result<()>;
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> io::Result<()>;
+    fn draw(&mut self, frame: &mut Frame);
 }
 
 pub trait Filed<'a> {
     const NAME: &'a str;
+    /// Which way scores of this game improve.
+    const BETTER: Better = Better::Higher;
+    /// What a plausible attempt of this game looks like.
+    const RULES: Rules = Rules::NONE;
     type SaveState: serde::Deserialize<'a> + serde::Serialize + Debug;
 
     fn get_savestate(&self) -> Self::SaveState;
     fn from_savestate(savestate: Self::SaveState) -> Self;
 
-    fn save(&self) {
-        if let Some(file) = Self::get_save_file() {
-            let savestate = self.get_savestate();
-            if let Ok(json) = serde_json::to_string(&savestate) {
-                match fs::create_dir_all(Self::get_dir().unwrap()) {
-                    Ok(_) => (),
-                    Err(e) => Self::write_log(e.to_string()),
-                }
-                match fs::write(file, json) {
-                    Ok(_) => Self::write_log(format!("{savestate:?}")),
-                    Err(e) => Self::write_log(e.to_string()),
-                }
-            }
+    fn save(&self)
+    where
+        Self::SaveState: serde::de::DeserializeOwned,
+    {
+        let savestate = self.get_savestate();
+        match Self::write_savestate(&savestate) {
+            Ok(()) => Self::write_log(format!("{savestate:?}")),
+            Err(e) => Self::write_log(e),
+        }
+    }
+
+    /// Writes `savestate` to the save file, unless that file holds a history that can't be read.
+    fn write_savestate(savestate: &Self::SaveState) -> Result<(), String>
+    where
+        Self::SaveState: serde::de::DeserializeOwned,
+    {
+        let file = Self::get_save_file()
+            .ok_or_else(|| String::from("could not find the data directory"))?;
+        // A file that can't be read still holds the player's history, so it stays until
+        // they've backed it up.
+        if let Err(e) = Self::read_savestate() {
+            return Err(format!("not overwriting {file}: {e}"));
         }
+
+        let json = serde_json::to_value(savestate)
+            .and_then(|value| serde_json::to_string(&migration::stamp(value)))
+            .map_err(|e| e.to_string())?;
+        if let Some(dir) = Path::new(&file).parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        storage::write(&file, &json).map_err(|e| e.to_string())
     }
 
     fn load() -> Option<Self>
@@ -59,65 +112,174 @@ pub trait Filed<'a> {
         Self: std::marker::Sized,
         Self::SaveState: serde::de::DeserializeOwned,
     {
-        let file = Self::get_save_file()?;
-        match std::fs::read_to_string(&file) {
-            Ok(contents) => {
-                let thing: serde_json::Result<Self::SaveState> = serde_json::from_str(&contents);
-                match thing {
-                    Ok(savestate) => return Some(Self::from_savestate(savestate)),
-                    Err(e) => Self::write_log(e.to_string()),
-                }
+        Some(Self::from_savestate(Self::load_savestate()?))
+    }
+
+    /// Save state from the save file, or `None` if there is none. A save file that can't be
+    /// read gets logged, and its newest backup that can be read takes its place.
+    fn load_savestate() -> Option<Self::SaveState>
+    where
+        Self::SaveState: serde::de::DeserializeOwned,
+    {
+        match Self::read_savestate() {
+            Ok(savestate) => savestate,
+            Err(e) => {
+                Self::write_log(e);
+                let (backup, savestate) = Self::read_backup()?;
+                Self::write_log(format!("recovered {savestate:?} from {backup}"));
+                Some(savestate)
             }
-            Err(e) => Self::write_log(e.to_string()),
         }
-        None
     }
 
+    /// Reads the save file, upgrading it from older versions. `Ok(None)` means there is no save
+    /// file yet, an error that there is one but it can't be used.
+    fn read_savestate() -> Result<Option<Self::SaveState>, String>
+    where
+        Self::SaveState: serde::de::DeserializeOwned,
+    {
+        Self::read_player_savestate(&players::active())
+    }
+
+    /// Like `read_savestate`, for the save file of any player.
+    fn read_player_savestate(player: &str) -> Result<Option<Self::SaveState>, String>
+    where
+        Self::SaveState: serde::de::DeserializeOwned,
+    {
+        match Self::get_player_file(player) {
+            Some(file) => storage::read(&file),
+            None => Ok(None),
+        }
+    }
+
+    /// Newest backup of the save file that can be read, and where it is.
+    fn read_backup() -> Option<(String, Self::SaveState)>
+    where
+        Self::SaveState: serde::de::DeserializeOwned,
+    {
+        let file = Self::get_save_file()?;
+        (1..=storage::BACKUPS).find_map(|n| {
+            let backup = storage::backup(&file, n);
+            let savestate = storage::read(&backup).ok()??;
+            Some((backup, savestate))
+        })
+    }
+
+    /// Save file of the player who is playing.
     fn get_save_file() -> Option<String> {
-        let dirs = BaseDirs::new()?;
-        let dir = dirs.data_dir();
+        Self::get_player_file(&players::active())
+    }
+
+    fn get_player_file(player: &str) -> Option<String> {
         Some(
-            dir.join(format!("{DIR_NAME}/{}.json", Self::NAME))
+            players::dir(player)?
+                .join(format!("{}.json", Self::NAME))
                 .to_str()?
                 .to_owned(),
         )
     }
 
     fn get_dir() -> Option<String> {
-        let dirs = BaseDirs::new()?;
-        let dir = dirs.data_dir();
-        Some(dir.join(DIR_NAME).to_str()?.to_owned())
+        Some(data_dir()?.to_str()?.to_owned())
     }
 
     fn write_log(log: String) {
-        if let Some(file) = get_log_file() {
-            if let Ok(data_file) = &mut OpenOptions::new().append(true).create(true).open(file) {
-                let now: DateTime<Local> = Local::now();
-                let log = format!(
-                    "[{}] {}: {}\n",
-                    now.format("%Y-%m-%d %H:%M:%S"),
-                    Self::NAME,
-                    log
-                );
-                let _ = data_file.write(log.as_bytes());
-            }
+        if let Some(file) = get_log_file()
+            && let Ok(data_file) = &mut OpenOptions::new().append(true).create(true).open(file)
+        {
+            let now: DateTime<Local> = Local::now();
+            let log = format!(
+                "[{}] {}: {}\n",
+                now.format("%Y-%m-%d %H:%M:%S"),
+                Self::NAME,
+                log
+            );
+            let _ = data_file.write(log.as_bytes());
         }
     }
 }
 
 const DIR_NAME: &str = "HumanBenchmark";
 
-#[derive(Default)]
+/// Directory everything is saved in.
+#[cfg(not(test))]
+fn data_dir() -> Option<PathBuf> {
+    Some(directories::BaseDirs::new()?.data_dir().join(DIR_NAME))
+}
+
+/// Each test saves in a directory of its own, away from the player's results and from the
+/// other tests running next to it.
+#[cfg(test)]
+fn data_dir() -> Option<PathBuf> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static DIR: PathBuf = std::env::temp_dir().join(format!(
+            "{DIR_NAME}-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+    }
+    Some(DIR.with(PathBuf::clone))
+}
+
+/// Runs a game, unless its save file can't be read and the player would rather keep it than
+/// back it up and start over.
+pub fn play(
+    entry: &registry::Entry,
+    terminal: &mut DefaultTerminal,
+    events: &mut dyn EventSource,
+) -> io::Result<()> {
+    if let Err(error) = (entry.read)()
+        && !Recovery::run(entry, error, terminal, events)?
+    {
+        return Ok(());
+    }
+    (entry.run)(terminal, events)
+}
+
+const COLUMNS: usize = 3;
+
 pub struct Menu {
     exit: bool,
-    index: i8,
+    index: Option<usize>,
+    /// Tiles by registry index.
+    regions: Regions<usize>,
+    /// Save states in registry order, shown on the tiles.
+    savestates: Vec<SaveState>,
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            index: Some(0),
+            regions: Regions::default(),
+            savestates: Menu::load_savestates(),
+        }
+    }
 }
 
 impl Menu {
-    fn key_event(&mut self, key_event: KeyEvent, terminal: &mut DefaultTerminal) -> io::Result<()> {
+    fn key_event(
+        &mut self,
+        key_event: KeyEvent,
+        terminal: &mut DefaultTerminal,
+        events: &mut dyn EventSource,
+    ) -> io::Result<()> {
         match key_event.code {
             KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
-            KeyCode::Enter => self.go(terminal)?,
+            KeyCode::Enter => self.go(terminal, events)?,
+            KeyCode::Char('s') => {
+                Dashboard::run(terminal, events)?;
+                self.savestates = Menu::load_savestates();
+            }
+            KeyCode::Char('p') => Profile::run(terminal, events)?,
+            KeyCode::Char('u') => {
+                Players::run(terminal, events)?;
+                self.savestates = Menu::load_savestates();
+            }
             KeyCode::Right => self.increase(),
             KeyCode::Left => self.decrease(),
             KeyCode::Up => self.up(),
@@ -131,145 +293,158 @@ impl Menu {
         &mut self,
         mouse_event: MouseEvent,
         terminal: &mut DefaultTerminal,
+        events: &mut dyn EventSource,
     ) -> io::Result<()> {
-        self.mouse_index(mouse_event, terminal);
+        self.index = self.regions.at(mouse_event.column, mouse_event.row);
         if let MouseEventKind::Down(event::MouseButton::Left) = mouse_event.kind {
-            self.go(terminal)?;
+            self.go(terminal, events)?;
         }
         Ok(())
     }
 
-    fn mouse_index(&mut self, mouse_event: MouseEvent, terminal: &mut DefaultTerminal) {
-        let area = terminal.get_frame().area();
-        let vert = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3), // Title
-                Constraint::Min(0),    // ---
-                Constraint::Length(5), // Top
-                Constraint::Length(5), // Mid
-                Constraint::Length(5), // Bot
-                Constraint::Min(0),    // ---
-            ])
-            .split(area);
-
-        let top = &Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Min(0),         // ---
-                Constraint::Percentage(20), // Left
-                Constraint::Percentage(20), // Mid
-                Constraint::Percentage(20), // Right
-                Constraint::Min(0),         // ---
-            ])
-            .split(vert[2])[1..=3];
-        let mid = &Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Min(0),         // ---
-                Constraint::Percentage(20), // Left
-                Constraint::Percentage(20), // Mid
-                Constraint::Percentage(20), // Right
-                Constraint::Min(0),         // ---
-            ])
-            .split(vert[3])[1..=3];
-        let bot = &Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Min(0),         // ---
-                Constraint::Percentage(30), // Left
-                Constraint::Percentage(30), // Right
-                Constraint::Min(0),         // ---
-            ])
-            .split(vert[4])[1..=2];
+    fn go(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        events: &mut dyn EventSource,
+    ) -> io::Result<()> {
+        if let Some(entry) = self.index.and_then(|i| registry::GAMES.get(i)) {
+            play(entry, terminal, events)?;
+            self.savestates = Menu::load_savestates();
+        }
+        Ok(())
+    }
 
-        let rects = [top, mid, bot];
+    fn load_savestates() -> Vec<SaveState> {
+        registry::GAMES
+            .iter()
+            .map(|entry| (entry.load)().unwrap_or_default())
+            .collect()
+    }
 
-        let mouse_rect = Rect::new(mouse_event.column, mouse_event.row, 1, 1);
+    fn rows() -> usize {
+        registry::GAMES.len().div_ceil(COLUMNS)
+    }
 
-        for (i, row) in rects.iter().enumerate() {
-            for (j, rect) in row.iter().enumerate() {
-                if mouse_rect.intersects(*rect) {
-                    self.index = (i * 3 + j) as i8;
-                    return;
-                }
-            }
-        }
-        self.index = -1;
-    }
-
-    fn go(&self, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        match self.index {
-            0 => reaction_time::ReactionTime::run(terminal)?,
-            1 => sequence_memory::SequenceMemory::run(terminal)?,
-            2 => aim_trainer::AimTrainer::run(terminal)?,
-            3 => number_memory::NumberMemory::run(terminal)?,
-            4 => verbal_memory::VerbalMemory::run(terminal)?,
-            5 => chimp_test::ChimpTest::run(terminal)?,
-            7 => typing_test::TypingTest::run(terminal)?,
-            _ => (),
-        }
-        Ok(())
+    fn row_len(row: usize) -> usize {
+        (registry::GAMES.len() - row * COLUMNS).min(COLUMNS)
     }
 
     fn increase(&mut self) {
-        if self.index % 3 != 2 && self.index < 7 {
-            self.index += 1;
+        let i = self.index.unwrap_or(0);
+        if i % COLUMNS + 1 < Menu::row_len(i / COLUMNS) {
+            self.index = Some(i + 1);
+        } else {
+            self.index = Some(i);
         }
     }
 
     fn decrease(&mut self) {
-        if self.index % 3 != 0 && self.index > 0 {
-            self.index -= 1;
+        let i = self.index.unwrap_or(0);
+        if !i.is_multiple_of(COLUMNS) {
+            self.index = Some(i - 1);
+        } else {
+            self.index = Some(i);
         }
     }
 
     fn down(&mut self) {
-        if self.index > 5 {
-            return;
-        }
-        self.index += 3;
-        if self.index > 7 {
-            self.index = 7;
+        let i = self.index.unwrap_or(0);
+        if i / COLUMNS + 1 < Menu::rows() {
+            self.index = Some(Menu::move_row(i, i / COLUMNS + 1));
+        } else {
+            self.index = Some(i);
         }
     }
 
     fn up(&mut self) {
-        self.index -= 3;
-        if self.index < 0 {
-            self.index = 0;
-        } else if self.index == 4 {
-            self.index = 5;
+        let i = self.index.unwrap_or(0);
+        if i / COLUMNS > 0 {
+            self.index = Some(Menu::move_row(i, i / COLUMNS - 1));
+        } else {
+            self.index = Some(i);
         }
     }
+
+    /// Moves to the tile of another row that sits closest to the center of the current one,
+    /// since rows with fewer tiles are centered.
+    fn move_row(i: usize, row: usize) -> usize {
+        let (from, to) = (Menu::row_len(i / COLUMNS), Menu::row_len(row));
+        let col = ((2 * (i % COLUMNS) + 1) * to) / (2 * from);
+        row * COLUMNS + col
+    }
+
+    /// Lays out every tile, in registry order.
+    fn layout(area: Rect) -> Regions<usize> {
+        let rows = Menu::rows();
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [Constraint::Length(3), Constraint::Min(0)]
+                    .into_iter()
+                    .chain((0..rows).map(|_| Constraint::Length(5)))
+                    .chain([Constraint::Min(0), Constraint::Length(1)]),
+            )
+            .split(area);
+
+        (0..rows)
+            .flat_map(|row| {
+                let len = Menu::row_len(row);
+                let width = 60 / len as u16;
+                Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(
+                        [Constraint::Min(0)]
+                            .into_iter()
+                            .chain((0..len).map(|_| Constraint::Percentage(width)))
+                            .chain([Constraint::Min(0)]),
+                    )
+                    .split(vert[row + 2])[1..=len]
+                    .to_vec()
+            })
+            .enumerate()
+            .collect()
+    }
 }
 
-impl Game for Menu {
-    fn run(terminal: &mut DefaultTerminal) -> io::Result<()> {
+impl Menu {
+    /// Unlike a [`Game`], the menu runs on the real terminal only, which it hands to the games
+    /// through the registry.
+    pub fn run(terminal: &mut DefaultTerminal, events: &mut dyn EventSource) -> io::Result<()> {
+        // Whoever shares the machine says who they are first, unless they did with `--player`.
+        if !players::picked() && players::list().len() > 1 {
+            Players::run(terminal, events)?;
+        }
         let mut menu = Menu::default();
 
         while !menu.exit {
             terminal.draw(|frame| menu.draw(frame))?;
-            menu.handle_input(terminal)?;
+            menu.handle_input(terminal, events)?;
             continue;
         }
 
         Ok(())
     }
 
-    fn handle_input(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        if event::poll(Duration::MAX)? {
-            match event::read()? {
-                event::Event::Key(key_event) => self.key_event(key_event, terminal)?,
-                event::Event::Mouse(mouse_event) => self.mouse_event(mouse_event, terminal)?,
+    fn handle_input(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        events: &mut dyn EventSource,
+    ) -> io::Result<()> {
+        if events.poll(Duration::MAX)? {
+            match events.read()? {
+                event::Event::Key(key_event) => self.key_event(key_event, terminal, events)?,
+                event::Event::Mouse(mouse_event) => {
+                    self.mouse_event(mouse_event, terminal, events)?
+                }
                 _ => (),
             }
         }
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = Menu::layout(frame.area());
+        frame.render_widget(&*self, frame.area());
     }
 }
 
@@ -283,98 +458,297 @@ impl Widget for &Menu {
             .constraints([
                 Constraint::Length(3), // Title
                 Constraint::Min(0),    // ---
-                Constraint::Length(5), // Top
-                Constraint::Length(5), // Mid
-                Constraint::Length(5), // Bot
-                Constraint::Min(0),    // ---
+                Constraint::Length(1), // Description
             ])
             .split(area);
 
-        let top_row = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Min(0),         // ---
-                Constraint::Percentage(20), // Left
-                Constraint::Percentage(20), // Mid
-                Constraint::Percentage(20), // Right
-                Constraint::Min(0),         // ---
-            ])
-            .split(vert[2]);
-
-        let mid_row = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Min(0),         // ---
-                Constraint::Percentage(20), // Left
-                Constraint::Percentage(20), // Mid
-                Constraint::Percentage(20), // Right
-                Constraint::Min(0),         // ---
-            ])
-            .split(vert[3]);
-
-        let bot_row = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Min(0),         // ---
-                Constraint::Percentage(30), // Left
-                Constraint::Percentage(30), // Right
-                Constraint::Min(0),         // ---
-            ])
-            .split(vert[4]);
-
         // title
         Paragraph::new("HumanBenchmark-CLI")
             .set_style(Color::Blue)
             .centered()
-            .block(Block::bordered().border_set(border::DOUBLE))
+            .block(
+                Block::bordered()
+                    .border_set(border::DOUBLE)
+                    .title_bottom(Line::from(" s: stats, p: profile, u: player ").left_aligned())
+                    .title_bottom(Line::from(format!(" {} ", players::active())).centered())
+                    .title_bottom(Line::from(format!(" seed {} ", rng::seed())).right_aligned()),
+            )
             .render(vert[0], buf);
 
-        // top row
-        widget("Reaction Time", self.index == 0, top_row[1], buf);
-        widget("Sequence Memory", self.index == 1, top_row[2], buf);
-        widget("Aim Trainer", self.index == 2, top_row[3], buf);
-
-        // mid row
-        widget("Number memory", self.index == 3, mid_row[1], buf);
-        widget("Verbal Memory", self.index == 4, mid_row[2], buf);
-        widget("Chimp Test", self.index == 5, mid_row[3], buf);
+        for (i, rect) in self.regions.iter() {
+            widget(
+                &registry::GAMES[i],
+                &self.savestates[i],
+                self.index == Some(i),
+                rect,
+                buf,
+            );
+        }
 
-        // bot row
-        widget("Visual Memory", self.index == 6, bot_row[1], buf);
-        widget("Typing", self.index == 7, bot_row[2], buf);
+        if let Some(entry) = self.index.and_then(|i| registry::GAMES.get(i)) {
+            Paragraph::new(entry.description)
+                .set_style(Color::DarkGray)
+                .italic()
+                .centered()
+                .render(vert[2], buf);
+        }
     }
 }
 
-fn widget(text: &str, color: bool, area: Rect, buf: &mut ratatui::prelude::Buffer) {
-    if color {
-        Paragraph::new(text)
-            .set_style(Color::LightRed)
-            .centered()
-            .block(Block::bordered().border_set(border::THICK))
-            .render(area, buf);
+/// Menu tile of a game, with its personal best, average and a sparkline of recent attempts.
+fn widget(
+    entry: &registry::Entry,
+    savestate: &SaveState,
+    selected: bool,
+    area: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+) {
+    let block = Block::bordered().border_set(border::THICK);
+    let inner = block.inner(area);
+    let style = if selected {
+        Style::default().fg(Color::LightRed)
     } else {
-        Paragraph::new(text)
+        Style::default()
+    };
+    block.set_style(style).render(area, buf);
+
+    let [name, summary, spark] = Layout::vertical([Constraint::Length(1); 3]).areas(inner);
+    Paragraph::new(entry.name)
+        .set_style(style)
+        .centered()
+        .render(name, buf);
+
+    let Some(stats) = savestate.stats(entry.better) else {
+        Paragraph::new("not played yet")
+            .dark_gray()
             .centered()
-            .block(Block::bordered().border_set(border::THICK))
-            .render(area, buf);
-    }
+            .render(summary, buf);
+        return;
+    };
+    Paragraph::new(format!(
+        "best {:.0} avg {:.0}",
+        stats.best,
+        savestate.avg_score()
+    ))
+    .dark_gray()
+    .centered()
+    .render(summary, buf);
+
+    // Bars grow with how good a score is, so that taller is better for every game.
+    let counted: Vec<&Attempt> = savestate.counted().collect();
+    let recent = &counted[counted.len().saturating_sub(spark.width as usize)..];
+    let range = (stats.best - stats.worst).abs().max(f32::EPSILON);
+    let bars: Vec<u64> = recent
+        .iter()
+        .map(|a| 1 + ((a.score - stats.worst).abs() / range * 7.0).round() as u64)
+        .collect();
+    let width = bars.len() as u16;
+    Sparkline::default().data(&bars).max(8).green().render(
+        Rect {
+            x: spark.x + (spark.width - width) / 2,
+            width,
+            ..spark
+        },
+        buf,
+    );
 }
 
 fn get_log_file() -> Option<String> {
-    let dirs = BaseDirs::new()?;
-    let dir = dirs.data_dir();
-    Some(
-        dir.join(format!("{DIR_NAME}/logs.txt"))
-            .to_str()?
-            .to_owned(),
-    )
+    Some(data_dir()?.join("logs.txt").to_str()?.to_owned())
+}
+
+/// Chart shown on a results screen, switched with Tab.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsTab {
+    /// Where the score sits among everyone else's.
+    #[default]
+    Population,
+    /// The player's own scores over time.
+    Progress,
+}
+
+impl ResultsTab {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            ResultsTab::Population => ResultsTab::Progress,
+            ResultsTab::Progress => ResultsTab::Population,
+        };
+    }
+}
+
+/// What a results screen shows. Games keep it across rounds.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ResultsView {
+    pub tab: ResultsTab,
+    /// Whether the population chart also shows a histogram of the player's own scores.
+    pub histogram: bool,
+}
+
+impl ResultsView {
+    pub fn toggle_histogram(&mut self) {
+        self.histogram = !self.histogram;
+    }
+}
+
+/// Renders the tab bar of a results screen and the chart of the selected tab below it.
+fn render_results(
+    view: ResultsView,
+    score: f64,
+    savestate: &SaveState,
+    population: &Population,
+    main: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+) {
+    let flag = savestate.attempts.last().and_then(|a| a.flag);
+    let vert = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(flag.is_some() as u16),
+            Constraint::Min(0),
+        ])
+        .split(main);
+
+    Tabs::new(["Population", "Progress"])
+        .select(view.tab as usize)
+        .highlight_style(Color::LightRed)
+        .render(vert[0], buf);
+    let hint = match view.tab {
+        ResultsTab::Population => "h: your scores, Tab: switch",
+        ResultsTab::Progress => "Tab: switch",
+    };
+    Line::from(hint)
+        .set_style(Color::DarkGray)
+        .italic()
+        .right_aligned()
+        .render(vert[0], buf);
+
+    // Where the attempt just played sits in the current session.
+    let phase = match sessions::phase(&savestate.attempts, population.better()) {
+        Some(Phase::WarmUp { attempt, of }) => {
+            Some(format!("Warming up ({attempt} of ~{of})").yellow())
+        }
+        Some(Phase::Fatigue(slope)) => Some(format!("Tiring ({slope:+.1} per attempt)").magenta()),
+        None => None,
+    };
+    if let Some(phase) = phase {
+        Line::from(phase).centered().render(vert[0], buf);
+    }
+    if let Some(flag) = flag {
+        Line::from(format!("Not counted: {}", flag.reason()).red())
+            .centered()
+            .render(vert[1], buf);
+    }
+
+    match view.tab {
+        ResultsTab::Population => {
+            let histogram = view.histogram.then(|| {
+                let scores: Vec<f32> = savestate.counted().map(|a| a.score).collect();
+                population.histogram(&scores)
+            });
+            render_graph(
+                savestate.avg_score() as f64,
+                score,
+                savestate.stats(population.better()),
+                population,
+                histogram.as_deref(),
+                vert[2],
+                buf,
+            )
+        }
+        ResultsTab::Progress => render_progress(&savestate.attempts, vert[2], buf),
+    }
+}
+
+/// Plots every recorded score that counts against when it was played, with a rolling average on top.
+fn render_progress(attempts: &[Attempt], main: Rect, buf: &mut ratatui::prelude::Buffer) {
+    let mut attempts: Vec<&Attempt> = attempts.iter().filter(|a| a.counts()).collect();
+    if attempts.len() < 2 {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ])
+            .split(main);
+        Paragraph::new("Play a couple more times to see your progress")
+            .set_style(Color::DarkGray)
+            .italic()
+            .centered()
+            .render(vert[1], buf);
+        return;
+    }
+
+    attempts.sort_by_key(|a| a.timestamp);
+
+    let scores: Vec<(f64, f64)> = attempts
+        .iter()
+        .map(|a| (a.timestamp.timestamp() as f64, a.score as f64))
+        .collect();
+    let averages: Vec<(f64, f64)> = (0..scores.len())
+        .map(|i| {
+            let window = &scores[(i + 1).saturating_sub(stats::ROLLING)..=i];
+            let avg = window.iter().map(|(_, y)| y).sum::<f64>() / window.len() as f64;
+            (scores[i].0, avg)
+        })
+        .collect();
+
+    let (first, last) = (
+        attempts[0].timestamp,
+        attempts[attempts.len() - 1].timestamp,
+    );
+    let x_bounds = [
+        scores[0].0,
+        scores[scores.len() - 1].0.max(scores[0].0 + 1.0),
+    ];
+
+    let min = scores.iter().map(|&(_, y)| y).fold(f64::INFINITY, f64::min);
+    let max = scores
+        .iter()
+        .map(|&(_, y)| y)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let pad = ((max - min) * 0.05).max(1.0);
+    let y_bounds = [(min - pad).max(0.0), max + pad];
+
+    let x = Axis::default()
+        .bounds(x_bounds)
+        .labels([
+            first.format("%Y-%m-%d").to_string(),
+            last.format("%Y-%m-%d").to_string(),
+        ])
+        .set_style(Color::DarkGray);
+    let y = Axis::default()
+        .bounds(y_bounds)
+        .labels([format!("{:.0}", y_bounds[0]), format!("{:.0}", y_bounds[1])])
+        .set_style(Color::DarkGray);
+
+    Chart::new(vec![
+        Dataset::default()
+            .name("Score")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .red()
+            .data(&scores),
+        Dataset::default()
+            .name(format!("Last {} avg.", stats::ROLLING))
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .green()
+            .data(&averages),
+    ])
+    .x_axis(x)
+    .y_axis(y)
+    .hidden_legend_constraints((Constraint::Min(0), Constraint::Min(0)))
+    .render(main, buf);
 }
 
 fn render_graph(
     avg_score: f64,
     score: f64,
-    dataset: Dataset,
-    bounds: [f64; 2],
+    stats: Option<Stats>,
+    population: &Population,
+    histogram: Option<&[(f64, f64)]>,
     main: Rect,
     buf: &mut ratatui::prelude::Buffer,
 ) {
@@ -396,29 +770,143 @@ fn render_graph(
 
     let y = Axis::default().bounds([0.0, 1.0]);
 
-    let x = Axis::default().bounds(bounds);
+    let x = Axis::default().bounds(population.bounds());
+
+    let mut datasets = vec![population.dataset()];
+    if let Some(histogram) = histogram {
+        datasets.push(
+            Dataset::default()
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .magenta()
+                .data(histogram),
+        );
+    }
+    datasets.extend([avg_score_dataset, score_dataset]);
 
-    Chart::new(vec![dataset, avg_score_dataset, score_dataset])
-        .y_axis(y)
-        .x_axis(x)
-        .render(main, buf);
+    Chart::new(datasets).y_axis(y).x_axis(x).render(main, buf);
+
+    let mut legend = vec![
+        ("---".set_style(Color::Red) + format!(" Score ({:.1})", score).into()).white(),
+        ("---".set_style(Color::Green) + format!(" Avg. score ({:.1})", avg_score).into()).white(),
+    ];
+    if histogram.is_some() {
+        legend.push(("---".set_style(Color::Magenta) + " Your scores".into()).white());
+    }
+    legend.push(Line::from(format!("    {}", population.describe(score))).bold());
+    if let Some(stats) = stats {
+        legend.extend(
+            [
+                format!("    Best ({:.1})", stats.best),
+                format!("    Worst ({:.1})", stats.worst),
+                format!("    Median ({:.1})", stats.median),
+                format!("    Std. dev. ({:.1})", stats.std_dev),
+                format!("    Last {} avg. ({:.1})", stats::ROLLING, stats.recent),
+            ]
+            .map(|stat| Line::from(stat).dark_gray()),
+        );
+    }
 
-    let avg_string = format!(" Avg. score ({:.1})", avg_score);
-    let string = format!(" Score ({:.1})", score);
+    let width = legend.iter().map(Line::width).max().unwrap_or_default();
 
     let hort = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Min(0),
-            Constraint::Length(avg_string.len().max(string.len()) as u16 + 4),
-        ])
+        .constraints([Constraint::Min(0), Constraint::Length(width as u16 + 1)])
         .split(main);
 
     let lines = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(1), Constraint::Length(1)])
+        .constraints(legend.iter().map(|_| Constraint::Length(1)))
         .split(hort[1]);
 
-    ("---".set_style(Color::Red) + string.into()).white().render(lines[0], buf);
-    ("---".set_style(Color::Green) + avg_string.into()).white().render(lines[1], buf);
+    for (line, rect) in legend.into_iter().zip(lines.iter()) {
+        line.render(*rect, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typing_test::TypingTest;
+
+    /// Save file of a game, with the directory it goes in.
+    fn save_file() -> String {
+        let file = TypingTest::get_save_file().unwrap();
+        fs::create_dir_all(Path::new(&file).parent().unwrap()).unwrap();
+        file
+    }
+
+    #[test]
+    fn save_file_from_before_versions_is_read() {
+        let file = save_file();
+        fs::write(&file, r#"{"avg_score": 80.0, "num_entries": 3}"#).unwrap();
+
+        let savestate = TypingTest::read_savestate().unwrap().unwrap();
+        assert_eq!(savestate.legacy_avg, 80.0);
+        assert_eq!(savestate.legacy_entries, 3);
+
+        TypingTest::write_savestate(&savestate).unwrap();
+        assert!(fs::read_to_string(&file).unwrap().contains("\"version\":1"));
+    }
+
+    #[test]
+    fn unreadable_save_file_is_not_overwritten() {
+        let file = save_file();
+        for contents in [
+            "{ not json",
+            r#"{"version": 99, "legacy_avg": 0.0, "legacy_entries": 0}"#,
+        ] {
+            fs::write(&file, contents).unwrap();
+            let error = TypingTest::write_savestate(&SaveState::default()).unwrap_err();
+            assert!(error.starts_with("not overwriting"), "{error}");
+            assert_eq!(fs::read_to_string(&file).unwrap(), contents);
+        }
+    }
+
+    /// Where each arrow key leads from tile `i`, as (left, right, up, down).
+    fn moves(i: usize) -> (usize, usize, usize, usize) {
+        let mut menu = Menu {
+            exit: false,
+            index: None,
+            regions: Regions::default(),
+            savestates: Vec::new(),
+        };
+        let mut step = |go: fn(&mut Menu)| {
+            menu.index = Some(i);
+            go(&mut menu);
+            menu.index.unwrap()
+        };
+        (
+            step(Menu::decrease),
+            step(Menu::increase),
+            step(Menu::up),
+            step(Menu::down),
+        )
+    }
+
+    #[test]
+    fn rows_move_to_the_closest_tile() {
+        // Eight games make two full rows of three and a centered one of two.
+        assert_eq!(Menu::rows(), 3);
+        assert_eq!(Menu::row_len(2), 2);
+
+        assert_eq!(moves(1), (0, 2, 1, 4));
+        // The last row sits between the columns above it.
+        assert_eq!(moves(3).3, 6);
+        assert_eq!(moves(4).3, 7);
+        assert_eq!(moves(5).3, 7);
+        assert_eq!(moves(6).2, 3);
+        assert_eq!(moves(7).2, 5);
+    }
+
+    #[test]
+    fn moves_stop_at_the_edges_instead_of_wrapping() {
+        assert_eq!(moves(0), (0, 1, 0, 3));
+        assert_eq!(moves(2), (1, 2, 2, 5));
+        // Neither to the next row nor around to the first one.
+        assert_eq!(moves(5).1, 5);
+        assert_eq!(moves(3).0, 3);
+        assert_eq!(moves(7), (6, 7, 5, 7));
+        assert_eq!(moves(6), (6, 7, 3, 6));
+    }
 }
@@ -3,61 +3,127 @@ mod mode;
 use mode::Mode;
 use std::{
     io,
+    sync::LazyLock,
     time::{Duration, Instant},
 };
 
-use rand::{Rng, rng};
+use rand::Rng;
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame, Terminal,
+    backend::Backend,
     buffer::Buffer,
     crossterm::event::{self, KeyCode, MouseEvent, MouseEventKind},
     layout::{Constraint, Direction, Layout, Position, Rect},
     style::{Color, Style, Styled, Stylize},
-    symbols::{Marker, border},
+    symbols::border,
     text::Span,
-    widgets::{Block, Dataset, GraphType, Paragraph, Widget},
+    widgets::{Block, Paragraph, Widget},
 };
 
-use super::{Filed, Game, render_graph, savestate::SaveState};
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, plausibility::Rules,
+    population::Population, regions::Regions, render_results, rng::SessionRng,
+    savestate::SaveState, stats::Better,
+};
 
 const FILE_NAME: &str = "AimTrainer";
+const VARIANT: &str = "30 targets";
 const TARGET_AMOUNT: u64 = 30;
 const TARGET_SIZE: u16 = 3;
 const PF_WIDTH: u16 = 100;
 const PF_HEIGHT: u16 = 20;
 
-#[derive(Default)]
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<AimTrainer>(include_str!("../../data/population/AimTrainer.csv"))
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    Start,
+    Target,
+}
+
 pub struct AimTrainer {
     exit: bool,
 
     mode: Mode,
     target: Position,
     instant: Option<Instant>,
-    times: SaveState,
+    start: Option<Instant>,
+    times: Vec<f32>,
     savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
+    regions: Regions<Region>,
+}
+
+impl Default for AimTrainer {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            mode: Mode::default(),
+            target: Position::default(),
+            instant: None,
+            start: None,
+            times: Vec::new(),
+            savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng: SessionRng::attempt(FILE_NAME),
+            view: ResultsView::default(),
+            regions: Regions::default(),
+        }
+    }
 }
 
 impl AimTrainer {
-    fn mouse_input(&mut self, terminal: &mut DefaultTerminal, mouse: MouseEvent) {
+    fn mouse_input(&mut self, mouse: MouseEvent) {
         match self.mode {
             Mode::Waiting => {
-                if let MouseEventKind::Down(_) = mouse.kind {
-                } else {
-                    return;
+                if let MouseEventKind::Down(_) = mouse.kind
+                    && self.regions.at(mouse.column, mouse.row) == Some(Region::Start)
+                {
+                    self.mode = Mode::Playing;
+                    self.start = Some(self.clock.now());
+                    self.new_target();
                 }
+            }
+            Mode::Playing => {
+                if let MouseEventKind::Down(_) = mouse.kind
+                    && self.regions.at(mouse.column, mouse.row) == Some(Region::Target)
+                {
+                    self.update_times();
+                    self.new_target();
+                }
+            }
+            Mode::Results => {
+                if let MouseEventKind::Down(event::MouseButton::Left) = mouse.kind {
+                    self.reset();
+                }
+            }
+        }
+    }
 
-                let area = terminal.get_frame().area();
-                let vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Length(3), Constraint::Min(0)])
-                    .split(area);
+    /// Lays out the start button or the current target. Nothing is clickable while the window
+    /// is too small to fit the playfield.
+    fn layout(&self, area: Rect) -> Regions<Region> {
+        let mut regions = Regions::default();
+        if area.width < PF_WIDTH + 2 || area.height < PF_HEIGHT + 2 + 2 + 1 {
+            return regions;
+        }
 
-                let main_rec = vert[1].inner(ratatui::layout::Margin {
-                    horizontal: 1,
-                    vertical: 1,
-                });
+        let main = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area)[1]
+            .inner(ratatui::layout::Margin {
+                horizontal: 1,
+                vertical: 1,
+            });
 
-                let main = Layout::default()
+        match self.mode {
+            Mode::Waiting => {
+                let main_vert = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
                         Constraint::Min(0),
@@ -66,7 +132,7 @@ impl AimTrainer {
                         Constraint::Length(1),
                         Constraint::Min(0),
                     ])
-                    .split(main_rec);
+                    .split(main);
 
                 let rect = Layout::default()
                     .direction(Direction::Horizontal)
@@ -75,31 +141,11 @@ impl AimTrainer {
                         Constraint::Length(TARGET_SIZE * 2),
                         Constraint::Min(0),
                     ])
-                    .split(main[2]);
-
-                let mouse_rect = Rect::new(mouse.column, mouse.row, 1, 1);
+                    .split(main_vert[2]);
 
-                if mouse_rect.intersects(rect[1]) {
-                    self.mode = Mode::Playing;
-                    self.instant = Some(Instant::now());
-                    self.new_target();
-                }
+                regions.push(Region::Start, rect[1]);
             }
             Mode::Playing => {
-                if let MouseEventKind::Down(_) = mouse.kind {
-                } else {
-                    return;
-                }
-
-                let vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Length(3), Constraint::Min(0)])
-                    .split(terminal.get_frame().area());
-                let main = vert[1].inner(ratatui::layout::Margin {
-                    horizontal: 1,
-                    vertical: 1,
-                });
-
                 let pf_vert = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
@@ -134,24 +180,16 @@ impl AimTrainer {
                     ])
                     .split(target_vert)[1];
 
-                let mouse_rect = Rect::new(mouse.column, mouse.row, 1, 1);
-
-                if mouse_rect.intersects(target) {
-                    self.update_times();
-                    self.new_target();
-                }
-            }
-            Mode::Results => {
-                if let MouseEventKind::Down(event::MouseButton::Left) = mouse.kind {
-                    self.reset();
-                }
+                regions.push(Region::Target, target);
             }
+            Mode::Results => (),
         }
+        regions
     }
 
     fn new_target(&mut self) {
-        self.instant = Some(Instant::now());
-        let mut rng = rng();
+        self.instant = Some(self.clock.now());
+        let rng = &mut self.rng;
         self.target = Position {
             x: rng.random_range(0..(PF_WIDTH - TARGET_SIZE * 2)),
             y: rng.random_range(0..(PF_HEIGHT - TARGET_SIZE)),
@@ -160,17 +198,32 @@ impl AimTrainer {
 
     fn update_times(&mut self) {
         if let Some(val) = self.instant {
-            self.times.update(val.elapsed().as_millis() as f32);
-            if self.times.num_entries as u64 >= TARGET_AMOUNT {
+            self.times.push(self.clock.elapsed(val).as_millis() as f32);
+            if self.times.len() as u64 >= TARGET_AMOUNT {
                 self.mode = Mode::Results;
-                self.savestate.update(self.times.avg_score);
+                let attempt = self.savestate.update(
+                    self.avg_time(),
+                    VARIANT,
+                    self.start
+                        .map(|s| self.clock.elapsed(s))
+                        .unwrap_or_default(),
+                    self.rng.seed(),
+                );
+                attempt.trials = self.times.clone();
+                attempt.flag = Self::RULES.check(&self.times, false);
             }
         }
     }
 
+    fn avg_time(&self) -> f32 {
+        self.times.iter().sum::<f32>() / self.times.len().max(1) as f32
+    }
+
     fn reset(&mut self) {
         let df = Self {
-            savestate: self.savestate,
+            savestate: std::mem::take(&mut self.savestate),
+            clock: self.clock.clone(),
+            view: self.view,
             ..Default::default()
         };
         *self = df;
@@ -178,47 +231,61 @@ impl AimTrainer {
 }
 
 impl Game for AimTrainer {
-    fn run(terminal: &mut DefaultTerminal) -> io::Result<()> {
+    fn run<B: Backend>(terminal: &mut Terminal<B>, events: &mut dyn EventSource) -> io::Result<()> {
         let mut game = Self::load().unwrap_or_default();
+        game.clock = events.clock();
 
         while !game.exit {
             terminal.draw(|frame| game.draw(frame))?;
-            game.handle_input(terminal)?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
         }
 
         game.save();
         Ok(())
     }
 
-    fn handle_input(&mut self, terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
-        if event::poll(Duration::from_secs(10))? {
-            let event = event::read()?;
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> std::io::Result<()> {
+        if events.poll(Duration::from_secs(10))? {
+            let event = events.read()?;
             match event {
                 event::Event::Key(key) => match key.code {
                     KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
                     KeyCode::Char('r') => self.reset(),
+                    KeyCode::Tab if matches!(self.mode, Mode::Results) => self.view.tab.toggle(),
+                    KeyCode::Char('h') if matches!(self.mode, Mode::Results) => {
+                        self.view.toggle_histogram()
+                    }
                     _ => {}
                 },
-                event::Event::Mouse(mouse) => {
-                    self.mouse_input(terminal, mouse);
-                }
+                event::Event::Mouse(mouse) => self.mouse_input(mouse),
                 _ => (),
             }
         }
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = self.layout(frame.area());
+        frame.render_widget(&*self, frame.area());
     }
 }
 
 impl Filed<'_> for AimTrainer {
     const NAME: &'static str = FILE_NAME;
+    const BETTER: Better = Better::Lower;
+    /// Moving to a new target takes a person well over the floor, so faster hits are double
+    /// clicks or scripts.
+    const RULES: Rules = Rules {
+        floor: Some(100.0),
+        min_spread: Some(0.05),
+    };
     type SaveState = SaveState;
 
     fn get_savestate(&self) -> Self::SaveState {
-        self.savestate
+        self.savestate.clone()
     }
 
     fn from_savestate(savestate: Self::SaveState) -> Self {
@@ -270,109 +337,36 @@ impl Widget for &AimTrainer {
             Mode::Waiting => {
                 block.title("╡ Menu ╞").render(vert[1], buf);
 
-                let main_vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(1),
-                        Constraint::Length(TARGET_SIZE),
-                        Constraint::Length(1),
-                        Constraint::Min(0),
-                    ])
-                    .split(main);
-
-                let rect = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(TARGET_SIZE * 2),
-                        Constraint::Min(0),
-                    ])
-                    .split(main_vert[2]);
-
-                render_target(rect[1], buf);
-
-                Paragraph::new("Hit 30 targets in as short a time as possible")
-                    .set_style(Color::DarkGray)
-                    .italic()
-                    .centered()
-                    .render(main_vert[1], buf);
+                if let Some(start) = self.regions.get(Region::Start) {
+                    render_target(start, buf);
+
+                    let hint = Rect {
+                        y: start.y.saturating_sub(1),
+                        height: 1,
+                        ..main
+                    };
+                    Paragraph::new("Hit 30 targets in as short a time as possible")
+                        .set_style(Color::DarkGray)
+                        .italic()
+                        .centered()
+                        .render(hint, buf);
+                }
             }
             Mode::Playing => {
                 block.title("╡ Playing ╞").render(vert[1], buf);
 
-                let pf_vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(PF_HEIGHT),
-                        Constraint::Min(0),
-                    ])
-                    .split(main)[1];
-
-                let pf = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(PF_WIDTH),
-                        Constraint::Min(0),
-                    ])
-                    .split(pf_vert)[1];
-
-                let target_vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Length(self.target.y),
-                        Constraint::Length(TARGET_SIZE),
-                    ])
-                    .split(pf)[1];
-
-                let target = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Length(self.target.x),
-                        Constraint::Length(TARGET_SIZE * 2),
-                    ])
-                    .split(target_vert)[1];
-
-                render_target(target, buf);
+                if let Some(target) = self.regions.get(Region::Target) {
+                    render_target(target, buf);
+                }
             }
             Mode::Results => {
                 block.title("╡ Results ╞").render(vert[1], buf);
 
-                let dataset = Dataset::default()
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .cyan()
-                    .data(&[
-                        (0.0, (0.0 / 270.0)),
-                        (50.0, (0.0 / 270.0)),
-                        (100.0, (0.0 / 270.0)),
-                        (150.0, (0.0 / 270.0)),
-                        (200.0, (0.0 / 270.0)),
-                        (250.0, (5.0 / 270.0)),
-                        (300.0, (60.0 / 270.0)),
-                        (350.0, (205.0 / 270.0)),
-                        (400.0, (245.0 / 270.0)),
-                        (450.0, (220.0 / 270.0)),
-                        (500.0, (160.0 / 270.0)),
-                        (550.0, (116.0 / 270.0)),
-                        (600.0, (74.0 / 270.0)),
-                        (650.0, (45.0 / 270.0)),
-                        (700.0, (30.0 / 270.0)),
-                        (750.0, (25.0 / 270.0)),
-                        (800.0, (20.0 / 270.0)),
-                        (850.0, (15.0 / 270.0)),
-                        (900.0, (10.0 / 270.0)),
-                        (950.0, (5.0 / 270.0)),
-                        (1000.0, (0.0 / 270.0)),
-                    ]);
-
-                render_graph(
-                    self.savestate.avg_score as f64,
-                    self.times.avg_score as f64,
-                    dataset,
-                    [0.0, 1000.0],
+                render_results(
+                    self.view,
+                    self.avg_time() as f64,
+                    &self.savestate,
+                    &POPULATION,
                     main,
                     buf,
                 );
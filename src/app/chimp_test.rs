@@ -1,27 +1,39 @@
 mod mode;
 
 use mode::Mode;
-use rand::{Rng, rng};
+use rand::Rng;
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame, Terminal,
+    backend::Backend,
     crossterm::event::{self, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
     layout::{Constraint, Direction, Layout, Margin, Position, Rect},
     style::{Color, Style, Styled, Stylize},
-    symbols::{Marker, border},
+    symbols::border,
     text::Span,
-    widgets::{Block, Dataset, GraphType, Paragraph, Widget},
+    widgets::{Block, Paragraph, Widget},
+};
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
 };
-use std::time::Duration;
 
-use super::{Filed, Game, render_graph, savestate::SaveState};
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, population::Population,
+    regions::Regions, render_results, rng::SessionRng, savestate::SaveState,
+};
 
 const FILE_NAME: &str = "ChimpTest";
+const VARIANT: &str = "standard";
 const HEIGHT: u16 = 5 * TARGET_SIZE;
 const WIDTH: u16 = 8 * TARGET_SIZE * 2;
 const TARGET_SIZE: u16 = 3;
 const LIVES: u32 = 0;
 const DEFAULT_NUMBERS: u32 = 4;
 
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<ChimpTest>(include_str!("../../data/population/ChimpTest.csv"))
+});
+
 #[derive(Debug, Clone)]
 pub struct ChimpTest {
     exit: bool,
@@ -30,8 +42,13 @@ pub struct ChimpTest {
     numbers: u32,
 
     target_vec: Vec<Position>,
+    start: Option<Instant>,
     savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
     mode: Mode,
+    regions: Regions<usize>,
 }
 
 impl Default for ChimpTest {
@@ -42,8 +59,13 @@ impl Default for ChimpTest {
             current_number: 0,
             exit: false,
             target_vec: Vec::default(),
+            start: None,
             savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng: SessionRng::attempt(FILE_NAME),
+            view: ResultsView::default(),
             mode: Mode::default(),
+            regions: Regions::default(),
         }
     }
 }
@@ -51,7 +73,9 @@ impl Default for ChimpTest {
 impl ChimpTest {
     fn reset(&mut self) {
         let new = Self {
-            savestate: self.savestate,
+            savestate: std::mem::take(&mut self.savestate),
+            clock: self.clock.clone(),
+            view: self.view,
             ..Default::default()
         };
         *self = new;
@@ -60,10 +84,12 @@ impl ChimpTest {
         match key.code {
             KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
             KeyCode::Char('r') => self.reset(),
+            KeyCode::Tab if self.mode == Mode::Results => self.view.tab.toggle(),
+            KeyCode::Char('h') if self.mode == Mode::Results => self.view.toggle_histogram(),
             _ => (),
         }
     }
-    fn mouse_event(&mut self, mouse: MouseEvent, terminal: &mut DefaultTerminal) {
+    fn mouse_event(&mut self, mouse: MouseEvent) {
         match mouse.kind {
             MouseEventKind::Down(event::MouseButton::Left) => (),
             _ => return,
@@ -72,82 +98,92 @@ impl ChimpTest {
         match self.mode {
             Mode::Waiting => {
                 self.mode = Mode::Playing;
+                self.start = Some(self.clock.now());
                 self.populate_vec();
             }
             Mode::Playing => {
-                let main = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Length(3), Constraint::Min(0)])
-                    .split(terminal.get_frame().area())[1]
-                    .inner(Margin {
-                        horizontal: 1,
-                        vertical: 1,
-                    });
-
-                let pf_vert = Layout::default()
+                if let Some(i) = self.regions.at(mouse.column, mouse.row) {
+                    if i == self.current_number {
+                        self.current_number += 1;
+                        if self.current_number == self.target_vec.len() {
+                            self.current_number = 0;
+                            self.numbers += 1;
+                            self.target_vec.clear();
+                            self.populate_vec();
+                        }
+                    } else {
+                        self.lose_life();
+                    }
+                }
+            }
+            Mode::Results => {
+                self.reset();
+            }
+        }
+    }
+    /// Lays out the targets that are still on the board, by their index in `target_vec`.
+    fn layout(&self, area: Rect) -> Regions<usize> {
+        if self.mode != Mode::Playing {
+            return Regions::default();
+        }
+
+        let main = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area)[1]
+            .inner(Margin {
+                horizontal: 1,
+                vertical: 1,
+            });
+
+        let pf_vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(HEIGHT),
+                Constraint::Min(0),
+            ])
+            .split(main)[1];
+
+        let pf = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(WIDTH),
+                Constraint::Min(0),
+            ])
+            .split(pf_vert)[1];
+
+        (self.current_number..self.target_vec.len())
+            .map(|i| {
+                let pos = self.target_vec[i];
+                let target_vert = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(HEIGHT),
+                        Constraint::Length(pos.y * TARGET_SIZE),
+                        Constraint::Length(TARGET_SIZE),
                         Constraint::Min(0),
                     ])
-                    .split(main)[1];
+                    .split(pf)[1];
 
-                let pf = Layout::default()
+                let target = Layout::default()
                     .direction(Direction::Horizontal)
                     .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(WIDTH),
+                        Constraint::Length(pos.x * TARGET_SIZE * 2),
+                        Constraint::Length(TARGET_SIZE * 2),
                         Constraint::Min(0),
                     ])
-                    .split(pf_vert)[1];
-
-                let mouse_rect = Rect::new(mouse.column, mouse.row, 1, 1);
-
-                for i in self.current_number..self.target_vec.len() {
-                    let pos = self.target_vec[i];
-                    let target_vert = Layout::default()
-                        .direction(Direction::Vertical)
-                        .constraints([
-                            Constraint::Length(pos.y * TARGET_SIZE),
-                            Constraint::Length(TARGET_SIZE),
-                        ])
-                        .split(pf)[1];
+                    .split(target_vert)[1];
 
-                    let target = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Length(pos.x * TARGET_SIZE * 2),
-                            Constraint::Length(TARGET_SIZE * 2),
-                            Constraint::Min(0),
-                        ])
-                        .split(target_vert)[1];
-
-                    if mouse_rect.intersects(target) {
-                        if i == self.current_number {
-                            self.current_number += 1;
-                            if self.current_number == self.target_vec.len() {
-                                self.current_number = 0;
-                                self.numbers += 1;
-                                self.target_vec.clear();
-                                self.populate_vec();
-                            }
-                        } else {
-                            self.lose_life();
-                        }
-                    }
-                }
-            }
-            Mode::Results => {
-                self.reset();
-            }
-        }
+                (i, target)
+            })
+            .collect()
     }
     fn populate_vec(&mut self) {
         let mut bools: [[bool; WIDTH as usize]; HEIGHT as usize] =
             [[false; WIDTH as usize]; HEIGHT as usize];
 
-        let mut rng = rng();
+        let rng = &mut self.rng;
         for _ in 1..=self.numbers {
             loop {
                 let pos: Position = Position {
@@ -171,29 +207,43 @@ impl ChimpTest {
             self.populate_vec();
         } else {
             self.mode = Mode::Results;
-            self.savestate.update(self.numbers as f32);
+            self.savestate.update(
+                self.numbers as f32,
+                VARIANT,
+                self.start
+                    .map(|s| self.clock.elapsed(s))
+                    .unwrap_or_default(),
+                self.rng.seed(),
+            );
         }
     }
 }
 
 impl Game for ChimpTest {
-    fn run(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
+    fn run<B: Backend>(
+        terminal: &mut Terminal<B>,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<()> {
         let mut game = Self::load().unwrap_or_default();
+        game.clock = events.clock();
 
         while !game.exit {
             terminal.draw(|frame| game.draw(frame))?;
-            game.handle_input(terminal)?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
         }
 
         game.save();
         Ok(())
     }
 
-    fn handle_input(&mut self, terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
-        if event::poll(Duration::MAX)? {
-            match event::read()? {
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> std::io::Result<()> {
+        if events.poll(Duration::MAX)? {
+            match events.read()? {
                 event::Event::Key(key) => self.key_event(key),
-                event::Event::Mouse(mouse) => self.mouse_event(mouse, terminal),
+                event::Event::Mouse(mouse) => self.mouse_event(mouse),
                 _ => (),
             }
         }
@@ -201,8 +251,9 @@ impl Game for ChimpTest {
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = self.layout(frame.area());
+        frame.render_widget(&*self, frame.area());
     }
 }
 
@@ -211,7 +262,7 @@ impl Filed<'_> for ChimpTest {
     const NAME: &'static str = FILE_NAME;
 
     fn get_savestate(&self) -> Self::SaveState {
-        self.savestate
+        self.savestate.clone()
     }
     fn from_savestate(savestate: Self::SaveState) -> Self {
         Self {
@@ -263,49 +314,7 @@ impl Widget for &ChimpTest {
             Mode::Playing => {
                 block.title("╡ Playing ╞").render(vert[1], buf);
 
-                let pf_vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(HEIGHT),
-                        Constraint::Min(0),
-                    ])
-                    .split(main)[1];
-
-                let pf = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(WIDTH),
-                        Constraint::Min(0),
-                    ])
-                    .split(pf_vert)[1];
-
-                // Block::bordered()
-                //     .border_set(border::ROUNDED)
-                //     .title(" Playfield ")
-                //     .render(pf_outer, buf);
-
-                for i in self.current_number..self.target_vec.len() {
-                    let pos = self.target_vec[i];
-                    let target_vert = Layout::default()
-                        .direction(Direction::Vertical)
-                        .constraints([
-                            Constraint::Length(pos.y * TARGET_SIZE),
-                            Constraint::Length(TARGET_SIZE),
-                            Constraint::Min(0),
-                        ])
-                        .split(pf)[1];
-
-                    let target = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Length(pos.x * TARGET_SIZE * 2),
-                            Constraint::Length(TARGET_SIZE * 2),
-                            Constraint::Min(0),
-                        ])
-                        .split(target_vert)[1];
-
+                for (i, target) in self.regions.iter() {
                     let pg = Layout::default()
                         .direction(Direction::Vertical)
                         .constraints([
@@ -339,39 +348,11 @@ impl Widget for &ChimpTest {
             Mode::Results => {
                 block.title("╡ Results ╞").render(vert[1], buf);
 
-                let dataset = Dataset::default()
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .cyan()
-                    .data(&[
-                        (4.0, (20.0 / 280.0)),
-                        (5.0, (14.0 / 280.0)),
-                        (6.0, (18.0 / 280.0)),
-                        (7.0, (42.0 / 280.0)),
-                        (8.0, (125.0 / 280.0)),
-                        (9.0, (240.0 / 280.0)),
-                        (10.0, (254.0 / 280.0)),
-                        (11.0, (219.0 / 280.0)),
-                        (12.0, (121.0 / 280.0)),
-                        (13.0, (59.0 / 280.0)),
-                        (14.0, (35.0 / 280.0)),
-                        (15.0, (20.0 / 280.0)),
-                        (16.0, (15.0 / 280.0)),
-                        (17.0, (10.0 / 280.0)),
-                        (18.0, (10.0 / 280.0)),
-                        (19.0, (5.0 / 280.0)),
-                        (20.0, (3.0 / 280.0)),
-                        (21.0, (2.0 / 280.0)),
-                        (22.0, (1.0 / 280.0)),
-                        (23.0, (1.0 / 280.0)),
-                        (24.0, (1.0 / 280.0)),
-                    ]);
-
-                render_graph(
-                    self.savestate.avg_score as f64,
+                render_results(
+                    self.view,
                     self.numbers as f64,
-                    dataset,
-                    [4.0, 24.0],
+                    &self.savestate,
+                    &POPULATION,
                     main,
                     buf,
                 );
@@ -387,3 +368,55 @@ fn num_to_string(num: u32) -> String {
         num.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+    use crate::app::events::ScriptedEvents;
+
+    /// Where the target showing `number` was last drawn.
+    fn find(terminal: &Terminal<TestBackend>, number: u32) -> (u16, u16) {
+        let buffer = terminal.backend().buffer();
+        let text = num_to_string(number);
+        (0..buffer.area.height)
+            .find_map(|y| {
+                let row: String = (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect();
+                let x = row[..row.find(&text)?].chars().count();
+                Some((x as u16, y))
+            })
+            .unwrap()
+    }
+
+    fn click(game: &mut ChimpTest, terminal: &mut Terminal<TestBackend>, (x, y): (u16, u16)) {
+        terminal.draw(|frame| game.draw(frame)).unwrap();
+        let mut events = ScriptedEvents::default().click(x, y);
+        game.handle_input(&mut events).unwrap();
+        terminal.draw(|frame| game.draw(frame)).unwrap();
+    }
+
+    #[test]
+    fn clicking_the_numbers_in_order() {
+        let mut game = ChimpTest::default();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+        click(&mut game, &mut terminal, (0, 0));
+        assert_eq!(game.mode, Mode::Playing);
+
+        for number in 1..=DEFAULT_NUMBERS {
+            let target = find(&terminal, number);
+            click(&mut game, &mut terminal, target);
+        }
+        assert_eq!(game.numbers, DEFAULT_NUMBERS + 1);
+        assert_eq!(game.current_number, 0);
+
+        // Out of order, with no lives left.
+        let target = find(&terminal, 2);
+        click(&mut game, &mut terminal, target);
+        assert_eq!(game.mode, Mode::Results);
+        assert_eq!(game.savestate.attempts.last().unwrap().score, 5.0);
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::{Duration, Instant};
+
+/// Source of time for everything that gets timed or scored.
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    /// The monotonic system clock.
+    #[default]
+    System,
+    /// A clock that only moves when it's told to. Clones share the same time.
+    #[cfg(test)]
+    Manual {
+        start: Instant,
+        offset: std::rc::Rc<std::cell::Cell<Duration>>,
+    },
+}
+
+impl Clock {
+    #[cfg(test)]
+    pub fn manual() -> Self {
+        Clock::Manual {
+            start: Instant::now(),
+            offset: Default::default(),
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        match self {
+            Clock::System => Instant::now(),
+            #[cfg(test)]
+            Clock::Manual { start, offset } => *start + offset.get(),
+        }
+    }
+
+    pub fn elapsed(&self, since: Instant) -> Duration {
+        self.now().saturating_duration_since(since)
+    }
+
+    /// Moves a manual clock forward. The system clock can't be moved, so it ignores this.
+    #[cfg(test)]
+    pub fn advance(&self, by: Duration) {
+        if let Clock::Manual { offset, .. } = self {
+            offset.set(offset.get().saturating_add(by));
+        }
+    }
+}
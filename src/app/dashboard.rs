@@ -0,0 +1,510 @@
+mod mode;
+
+use std::{fmt::Write as _, io, time::Duration};
+
+use mode::Mode;
+use ratatui::{
+    Frame, Terminal,
+    backend::Backend,
+    crossterm::event::{self, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style, Styled, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Bar, BarChart, BarGroup, Block, Paragraph, Row, Table, Widget},
+};
+
+use super::{
+    Game,
+    events::EventSource,
+    periods::{self, Period},
+    regions::Regions,
+    registry, render_progress,
+    savestate::{Attempt, SaveState},
+    sessions::{self, Trend},
+    stats::Better,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Saved results of every game on one screen, with the history of each one behind it.
+#[derive(Default)]
+pub struct Dashboard {
+    exit: bool,
+    mode: Mode,
+    /// Save states in registry order.
+    savestates: Vec<SaveState>,
+    selected: usize,
+    /// Number of attempts scrolled past in the history view.
+    scroll: usize,
+    /// Rows of the list, by registry index.
+    regions: Regions<usize>,
+}
+
+impl Dashboard {
+    fn key_event(&mut self, key: KeyEvent) {
+        match self.mode {
+            Mode::List => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+                KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected = (self.selected + 1).min(registry::GAMES.len() - 1);
+                }
+                KeyCode::Enter => self.open(self.selected),
+                _ => (),
+            },
+            Mode::History(game) => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => self.mode = Mode::List,
+                KeyCode::Char('t') => self.mode = Mode::Times(game, Period::Hour),
+                KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let attempts = self.savestates[game].attempts.len();
+                    self.scroll = (self.scroll + 1).min(attempts.saturating_sub(1));
+                }
+                _ => (),
+            },
+            Mode::Times(game, mut period) => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => {
+                    self.mode = Mode::History(game)
+                }
+                KeyCode::Char('t') | KeyCode::Tab => {
+                    period.toggle();
+                    self.mode = Mode::Times(game, period);
+                }
+                _ => (),
+            },
+        }
+    }
+
+    fn mouse_event(&mut self, mouse: MouseEvent) {
+        if let MouseEventKind::Down(event::MouseButton::Left) = mouse.kind
+            && let Some(game) = self.regions.at(mouse.column, mouse.row)
+        {
+            self.selected = game;
+            self.open(game);
+        }
+    }
+
+    fn open(&mut self, game: usize) {
+        self.mode = Mode::History(game);
+        self.scroll = 0;
+    }
+
+    /// Lays out the rows of the list, which are the only clickable part.
+    fn layout(&self, area: Rect) -> Regions<usize> {
+        if self.mode != Mode::List {
+            return Regions::default();
+        }
+
+        let table = Dashboard::body(area).inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+        // The header takes the first line.
+        (0..registry::GAMES.len())
+            .map(|i| {
+                (
+                    i,
+                    Rect::new(table.x, table.y + 1 + i as u16, table.width, 1),
+                )
+            })
+            .filter(|(_, rect)| table.contains(rect.as_position()))
+            .collect()
+    }
+
+    /// Area inside the outer block, below the title.
+    fn body(area: Rect) -> Rect {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+        vert[1].inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        })
+    }
+
+    fn render_list(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let rows = registry::GAMES
+            .iter()
+            .zip(&self.savestates)
+            .enumerate()
+            .map(|(i, (entry, savestate))| {
+                let dash = || String::from("-");
+                let stats = savestate.stats(entry.better);
+                let row = Row::new([
+                    entry.name.to_owned(),
+                    savestate.num_entries().to_string(),
+                    if savestate.num_entries() > 0 {
+                        format!("{:.1}", savestate.avg_score())
+                    } else {
+                        dash()
+                    },
+                    stats.map_or_else(dash, |s| format!("{:.1}", s.best)),
+                    if savestate.num_entries() > 0 {
+                        let avg = savestate.avg_score() as f64;
+                        format!("{:.0}%", entry.population.percentile(avg))
+                    } else {
+                        dash()
+                    },
+                    savestate
+                        .attempts
+                        .last()
+                        .map_or_else(dash, |a| a.timestamp.format(DATE_FORMAT).to_string()),
+                ]);
+                if i == self.selected {
+                    row.set_style(Color::LightRed)
+                } else {
+                    row
+                }
+            });
+
+        Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(11),
+                Constraint::Min(16),
+            ],
+        )
+        .header(
+            Row::new([
+                "Game",
+                "Attempts",
+                "Average",
+                "Best",
+                "Percentile",
+                "Last played",
+            ])
+            .bold(),
+        )
+        .block(Block::bordered().border_set(border::ROUNDED))
+        .render(area, buf);
+    }
+
+    fn render_history(&self, game: usize, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let entry = &registry::GAMES[game];
+        let savestate = &self.savestates[game];
+
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Percentage(45),
+                Constraint::Min(0),
+            ])
+            .split(area);
+
+        let summary = match savestate.stats(entry.better) {
+            Some(stats) => format!(
+                "{} attempts, avg. {:.1}, best {:.1}, worst {:.1}, median {:.1}, std. dev. {:.1}",
+                savestate.num_entries(),
+                savestate.avg_score(),
+                stats.best,
+                stats.worst,
+                stats.median,
+                stats.std_dev
+            ),
+            None => format!("{} attempts", savestate.num_entries()),
+        };
+        let flagged = savestate.attempts.len() - savestate.counted().count();
+        let summary = if flagged > 0 {
+            format!("{summary} ({flagged} more not counted)")
+        } else {
+            summary
+        };
+        Paragraph::new(vec![
+            Line::from(summary),
+            Line::from(session_summary(&savestate.attempts, entry.better)).dark_gray(),
+        ])
+        .centered()
+        .render(vert[0], buf);
+
+        render_progress(&savestate.attempts, vert[1], buf);
+
+        let rows = savestate
+            .attempts
+            .iter()
+            .rev()
+            .skip(self.scroll)
+            .map(|attempt| {
+                let row = Row::new([
+                    attempt.timestamp.format(DATE_FORMAT).to_string(),
+                    format!("{:.1}", attempt.score),
+                    attempt.variant.clone(),
+                    format!("{:.1} s", attempt.duration_ms as f64 / 1000.0),
+                    attempt.seed.map(|s| s.to_string()).unwrap_or_default(),
+                    attempt
+                        .flag
+                        .map(|f| f.reason())
+                        .unwrap_or_default()
+                        .to_owned(),
+                ]);
+                // Flagged attempts are listed but don't count.
+                if attempt.counts() {
+                    row
+                } else {
+                    row.dark_gray().crossed_out()
+                }
+            });
+
+        let mut block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title(format!(" {} ", savestate.attempts.len()));
+        if savestate.legacy_entries > 0 {
+            block = block.title_bottom(format!(
+                " plus {} older results averaging {:.1} ",
+                savestate.legacy_entries, savestate.legacy_avg
+            ));
+        }
+
+        Table::new(
+            rows,
+            [
+                Constraint::Length(17),
+                Constraint::Length(9),
+                Constraint::Length(14),
+                Constraint::Length(9),
+                Constraint::Length(21),
+                Constraint::Min(0),
+            ],
+        )
+        .header(
+            Row::new([
+                "Played",
+                "Score",
+                "Variant",
+                "Duration",
+                "Seed",
+                "Not counted",
+            ])
+            .bold(),
+        )
+        .block(block)
+        .render(vert[2], buf);
+    }
+
+    fn render_times(
+        &self,
+        game: usize,
+        period: Period,
+        area: Rect,
+        buf: &mut ratatui::prelude::Buffer,
+    ) {
+        let entry = &registry::GAMES[game];
+        let buckets = periods::buckets(&self.savestates[game].attempts, period);
+
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+
+        let best = buckets.iter().filter(|b| b.count > 0).reduce(|a, b| {
+            if entry.better.is_better(b.mean, a.mean) {
+                b
+            } else {
+                a
+            }
+        });
+        let summary = match best {
+            Some(best) => format!(
+                "Best {}: {} (avg. {:.1} over {} attempts)",
+                period.name(),
+                best.label,
+                best.mean,
+                best.count
+            ),
+            None => String::from("No attempts yet"),
+        };
+        Paragraph::new(summary).centered().render(vert[0], buf);
+
+        let bars: Vec<Bar> = buckets
+            .iter()
+            .map(|bucket| {
+                let bar = Bar::default()
+                    .value(bucket.mean.round() as u64)
+                    .label(Line::from(bucket.label.clone()));
+                if bucket.count == 0 {
+                    bar.text_value(String::new())
+                } else if best == Some(bucket) {
+                    bar.style(Color::LightRed)
+                } else {
+                    bar.style(Color::Green)
+                }
+            })
+            .collect();
+
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title(" Mean score ")
+            .title_bottom(Line::from(" attempts ").dark_gray());
+        let inner = block.inner(vert[1]);
+        block.render(vert[1], buf);
+
+        let [chart, counts] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+        let width = (inner.width / buckets.len() as u16)
+            .saturating_sub(1)
+            .max(1);
+        BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(width)
+            .bar_gap(1)
+            .render(chart, buf);
+
+        // Number of attempts under each bar, which the chart itself has no room for.
+        for (i, bucket) in buckets.iter().enumerate() {
+            let x = counts.x + i as u16 * (width + 1);
+            if x + width > counts.right() {
+                break;
+            }
+            Paragraph::new(bucket.count.to_string())
+                .dark_gray()
+                .centered()
+                .render(Rect::new(x, counts.y, width, 1), buf);
+        }
+    }
+}
+
+/// Warm-up and fatigue over every session long enough to tell.
+fn session_summary(attempts: &[Attempt], better: Better) -> String {
+    let all = sessions::split(attempts);
+    let trends: Vec<Trend> = all
+        .iter()
+        .filter(|session| session.len() >= sessions::MIN_ATTEMPTS)
+        .map(|session| Trend::of(session, better))
+        .collect();
+    let gap = sessions::gap().as_secs_f64() / 60.0;
+    let mut summary = format!("{} sessions ({gap:.0} min apart)", all.len());
+    if trends.is_empty() {
+        let _ = write!(
+            summary,
+            ", none of {}+ attempts to look for warm-up and fatigue in",
+            sessions::MIN_ATTEMPTS
+        );
+        return summary;
+    }
+
+    let warmups: Vec<usize> = trends.iter().map(|t| t.warmup).filter(|&w| w > 0).collect();
+    let _ = write!(
+        summary,
+        ", warm-up in {} of {}",
+        warmups.len(),
+        trends.len()
+    );
+    if !warmups.is_empty() {
+        let _ = write!(
+            summary,
+            " (~{:.0} attempts)",
+            warmups.iter().sum::<usize>() as f32 / warmups.len() as f32
+        );
+    }
+    let slopes: Vec<f32> = trends
+        .iter()
+        .filter_map(|t| t.fatigue.map(|(_, slope)| slope))
+        .collect();
+    let _ = write!(summary, ", fatigue in {}", slopes.len());
+    if !slopes.is_empty() {
+        let _ = write!(
+            summary,
+            " ({:+.1} per attempt)",
+            slopes.iter().sum::<f32>() / slopes.len() as f32
+        );
+    }
+    summary
+}
+
+impl Game for Dashboard {
+    fn run<B: Backend>(terminal: &mut Terminal<B>, events: &mut dyn EventSource) -> io::Result<()> {
+        let mut dashboard = Dashboard {
+            savestates: registry::GAMES
+                .iter()
+                .map(|entry| (entry.load)().unwrap_or_default())
+                .collect(),
+            ..Default::default()
+        };
+
+        while !dashboard.exit {
+            terminal.draw(|frame| dashboard.draw(frame))?;
+            dashboard.handle_input(events)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> io::Result<()> {
+        if events.poll(Duration::MAX)? {
+            match events.read()? {
+                event::Event::Key(key) => self.key_event(key),
+                event::Event::Mouse(mouse) => self.mouse_event(mouse),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = self.layout(frame.area());
+        frame.render_widget(&*self, frame.area());
+    }
+}
+
+impl Widget for &Dashboard {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        Paragraph::new(Span::from("Statistics").fg(Color::Red))
+            .centered()
+            .block(Block::bordered().border_set(border::DOUBLE))
+            .render(vert[0], buf);
+
+        let block = Block::bordered().border_set(border::DOUBLE);
+        let main = Dashboard::body(area);
+
+        match self.mode {
+            Mode::List => {
+                block
+                    .title("╡ Dashboard ╞")
+                    .title_bottom(
+                        Line::from(" ↑/↓ select, Enter: history, Esc: back ")
+                            .set_style(Style::default().fg(Color::DarkGray)),
+                    )
+                    .render(vert[1], buf);
+                self.render_list(main, buf);
+            }
+            Mode::History(game) => {
+                block
+                    .title(format!("╡ {} ╞", registry::GAMES[game].name))
+                    .title_bottom(
+                        Line::from(" ↑/↓ scroll, t: by time of day, Esc: back ")
+                            .set_style(Style::default().fg(Color::DarkGray)),
+                    )
+                    .render(vert[1], buf);
+                self.render_history(game, main, buf);
+            }
+            Mode::Times(game, period) => {
+                block
+                    .title(format!(
+                        "╡ {} by {} ╞",
+                        registry::GAMES[game].name,
+                        period.name()
+                    ))
+                    .title_bottom(
+                        Line::from(" t: hour/weekday, Esc: back ")
+                            .set_style(Style::default().fg(Color::DarkGray)),
+                    )
+                    .render(vert[1], buf);
+                self.render_times(game, period, main, buf);
+            }
+        }
+    }
+}
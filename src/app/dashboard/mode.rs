@@ -0,0 +1,12 @@
+use crate::app::periods::Period;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Every game at a glance.
+    #[default]
+    List,
+    /// Every attempt of the game at this registry index.
+    History(usize),
+    /// How the game at this registry index goes by hour of the day or day of the week.
+    Times(usize, Period),
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod scripted;
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use super::clock::Clock;
+use crate::terminal;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+#[cfg(test)]
+pub use scripted::ScriptedEvents;
+
+/// Where games get their input from.
+pub trait EventSource {
+    /// Waits up to `timeout` for an event, returning whether one is ready.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+    fn read(&mut self) -> io::Result<Event>;
+
+    /// Clock the time between events is measured on, which games time and score with.
+    fn clock(&self) -> Clock {
+        Clock::System
+    }
+}
+
+/// Reads events from the terminal. Key releases, which terminals that report repeats send as
+/// well, are left out, since no screen acts on them.
+#[derive(Default, Debug, Clone)]
+pub struct TerminalEvents {
+    /// Event that was read while polling, so that releases could be skipped.
+    pending: Option<Event>,
+}
+
+impl TerminalEvents {
+    fn is_release(event: &Event) -> bool {
+        matches!(event, Event::Key(key) if key.kind == KeyEventKind::Release)
+    }
+}
+
+impl EventSource for TerminalEvents {
+    /// Waits in short slices so that a termination signal interrupts even an endless wait.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.pending.is_some() {
+            return Ok(true);
+        }
+        let start = Instant::now();
+        loop {
+            if let Some(signal) = terminal::signal() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    format!("received signal {signal}"),
+                ));
+            }
+
+            let remaining = timeout.saturating_sub(start.elapsed());
+            if event::poll(remaining.min(terminal::SIGNAL_CHECK))? {
+                let event = event::read()?;
+                if !Self::is_release(&event) {
+                    self.pending = Some(event);
+                    return Ok(true);
+                }
+            }
+            if start.elapsed() >= timeout {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        if let Some(event) = self.pending.take() {
+            return Ok(event);
+        }
+        loop {
+            let event = event::read()?;
+            if !Self::is_release(&event) {
+                return Ok(event);
+            }
+        }
+    }
+}
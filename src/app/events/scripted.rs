@@ -0,0 +1,118 @@
+use std::{collections::VecDeque, io, time::Duration};
+
+use super::EventSource;
+use crate::app::clock::Clock;
+use ratatui::crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+/// Replays a fixed list of events, so that a game can be driven without a terminal.
+///
+/// Waiting in the script, or polling once it's empty, moves the attached clock forward. Polling
+/// an empty script with no timeout returns an `UnexpectedEof` error instead of hanging.
+#[derive(Default, Debug, Clone)]
+pub struct ScriptedEvents {
+    steps: VecDeque<Step>,
+    clock: Clock,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Event(Event),
+    /// Time that passes before the next event.
+    Wait(Duration),
+}
+
+impl ScriptedEvents {
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn key(mut self, code: KeyCode) -> Self {
+        self.steps
+            .push_back(Step::Event(Event::Key(KeyEvent::from(code))));
+        self
+    }
+
+    pub fn keys(self, codes: impl IntoIterator<Item = KeyCode>) -> Self {
+        codes.into_iter().fold(self, Self::key)
+    }
+
+    pub fn mouse(mut self, mouse: MouseEvent) -> Self {
+        self.steps.push_back(Step::Event(Event::Mouse(mouse)));
+        self
+    }
+
+    /// Left click at the given cell.
+    pub fn click(self, column: u16, row: u16) -> Self {
+        self.mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    /// Lets `time` pass on the clock before the next event comes in.
+    pub fn wait(mut self, time: Duration) -> Self {
+        self.steps.push_back(Step::Wait(time));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        let mut remaining = timeout;
+        loop {
+            match self.steps.front_mut() {
+                Some(Step::Event(_)) => return Ok(true),
+                Some(Step::Wait(time)) if *time > remaining => {
+                    *time -= remaining;
+                    self.clock.advance(remaining);
+                    return Ok(false);
+                }
+                // The event after a wait comes in just after it, not as the game stops waiting.
+                Some(Step::Wait(time)) if *time == remaining => {
+                    self.clock.advance(remaining);
+                    self.steps.pop_front();
+                    return Ok(false);
+                }
+                Some(Step::Wait(time)) => {
+                    let time = *time;
+                    self.clock.advance(time);
+                    remaining -= time;
+                    self.steps.pop_front();
+                }
+                None if timeout == Duration::MAX => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "ran out of scripted events",
+                    ));
+                }
+                None => {
+                    self.clock.advance(remaining);
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        match self.steps.pop_front() {
+            Some(Step::Event(event)) => Ok(event),
+            _ => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "ran out of scripted events",
+            )),
+        }
+    }
+
+    fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+}
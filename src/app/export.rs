@@ -0,0 +1,115 @@
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::Serialize;
+
+use super::{plausibility::Flag, registry::Entry, savestate::SaveState};
+
+/// Columns of a CSV export, in order. They're the fields of [`Record`], and only ever get added
+/// to at the end.
+pub const COLUMNS: [&str; 7] = [
+    "game",
+    "variant",
+    "score",
+    "unit",
+    "timestamp",
+    "duration_ms",
+    "flag",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(Format::Csv),
+            "jsonl" => Some(Format::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// One attempt as it's exported.
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    /// Name of the game on the command line.
+    pub game: String,
+    pub variant: String,
+    pub score: f32,
+    pub unit: String,
+    pub timestamp: DateTime<Local>,
+    pub duration_ms: u64,
+    /// Why the attempt doesn't count, if it doesn't.
+    pub flag: Option<Flag>,
+}
+
+/// Every attempt of the given games played since `since`, oldest first. Results from before
+/// attempts were kept on their own have no time or score of their own, so they're left out.
+pub fn records(games: &[(&Entry, SaveState)], since: Option<DateTime<Local>>) -> Vec<Record> {
+    let mut records: Vec<Record> = games
+        .iter()
+        .flat_map(|(entry, savestate)| {
+            savestate
+                .attempts
+                .iter()
+                .filter(move |attempt| since.is_none_or(|since| attempt.timestamp >= since))
+                .map(|attempt| Record {
+                    game: entry.id.to_owned(),
+                    variant: attempt.variant.clone(),
+                    score: attempt.score,
+                    unit: entry.unit.to_owned(),
+                    timestamp: attempt.timestamp,
+                    duration_ms: attempt.duration_ms,
+                    flag: attempt.flag,
+                })
+        })
+        .collect();
+    records.sort_by_key(|record| record.timestamp);
+    records
+}
+
+pub fn write(records: &[Record], format: Format) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Csv => {
+            let _ = writeln!(out, "{}", COLUMNS.join(","));
+            for record in records {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    field(&record.game),
+                    field(&record.variant),
+                    record.score,
+                    field(&record.unit),
+                    record
+                        .timestamp
+                        .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                    record.duration_ms,
+                    record.flag.map_or("", Flag::name),
+                );
+            }
+        }
+        Format::Jsonl => {
+            for record in records {
+                if let Ok(line) = serde_json::to_string(record) {
+                    let _ = writeln!(out, "{line}");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// A CSV field, quoted if it has to be.
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
@@ -0,0 +1,475 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Deserialize;
+
+use super::{
+    plausibility::Flag,
+    registry::{self, Entry},
+    savestate::Attempt,
+};
+
+/// Columns a file without a header is read as.
+const GENERIC_COLUMNS: [&str; 3] = ["game", "score", "timestamp"];
+
+/// What an import did, to show the player.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Attempts added to each game, in registry order.
+    pub imported: Vec<usize>,
+    /// Rows left out, by line number, and why.
+    pub skipped: Vec<(usize, String)>,
+    /// Attempts added that don't count towards averages, by line number, and why.
+    pub flagged: Vec<(usize, Flag)>,
+}
+
+impl Report {
+    pub fn total(&self) -> usize {
+        self.imported.iter().sum()
+    }
+}
+
+/// A row as it's read, before it's checked against its game. Exports have every field, the
+/// generic format only the game, the score and the timestamp.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Row {
+    game: String,
+    variant: String,
+    score: Option<f32>,
+    unit: String,
+    timestamp: String,
+    duration_ms: u64,
+    flag: Option<Flag>,
+}
+
+/// A time as RFC 3339, as `YYYY-MM-DD HH:MM[:SS]` in local time, or as a date, meaning its start.
+pub fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Local));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })?;
+    naive.and_local_timezone(Local).earliest()
+}
+
+/// Adds the attempts of an export (CSV or JSON Lines) or of a generic `game,score,timestamp`
+/// CSV to the saved results of the player who is playing. Attempts that are saved already are
+/// skipped, so importing the same file twice adds nothing.
+pub fn import(text: &str) -> Report {
+    let mut report = Report {
+        imported: vec![0; registry::GAMES.len()],
+        ..Report::default()
+    };
+
+    let rows = if text.trim_start().starts_with('{') {
+        read_jsonl(text, &mut report)
+    } else {
+        read_csv(text, &mut report)
+    };
+    let mut attempts: Vec<Vec<(usize, Attempt)>> = vec![Vec::new(); registry::GAMES.len()];
+    for (line, row) in rows {
+        match check(row) {
+            Ok((game, attempt)) => attempts[game].push((line, attempt)),
+            Err(e) => report.skipped.push((line, e)),
+        }
+    }
+
+    for (game, (entry, attempts)) in registry::GAMES.iter().zip(attempts).enumerate() {
+        if !attempts.is_empty() {
+            merge(game, entry, attempts, &mut report);
+        }
+    }
+    report.skipped.sort_by_key(|(line, _)| *line);
+    report.flagged.sort_by_key(|(line, _)| *line);
+    report
+}
+
+fn read_jsonl(text: &str, report: &mut Report) -> Vec<(usize, Row)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .filter_map(|(line, text)| match serde_json::from_str(text) {
+            Ok(row) => Some((line, row)),
+            Err(e) => {
+                report.skipped.push((line, e.to_string()));
+                None
+            }
+        })
+        .collect()
+}
+
+fn read_csv(text: &str, report: &mut Report) -> Vec<(usize, Row)> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .peekable();
+
+    // A header names the columns, in any order. Without one, the file is generic.
+    let mut columns: Vec<String> = GENERIC_COLUMNS.map(String::from).to_vec();
+    if let Some((_, first)) = lines.peek()
+        && let Ok(fields) = split(first)
+        && fields
+            .iter()
+            .any(|field| field.eq_ignore_ascii_case("game"))
+    {
+        columns = fields.iter().map(|field| field.to_lowercase()).collect();
+        lines.next();
+    }
+
+    lines
+        .filter_map(|(line, text)| match csv_row(&columns, text) {
+            Ok(row) => Some((line, row)),
+            Err(e) => {
+                report.skipped.push((line, e));
+                None
+            }
+        })
+        .collect()
+}
+
+fn csv_row(columns: &[String], line: &str) -> Result<Row, String> {
+    let fields = split(line)?;
+    if fields.len() < columns.len() {
+        return Err(format!(
+            "expected {} fields, found {}",
+            columns.len(),
+            fields.len()
+        ));
+    }
+
+    let mut row = Row::default();
+    for (column, field) in columns.iter().zip(fields) {
+        match column.as_str() {
+            "game" => row.game = field,
+            "variant" => row.variant = field,
+            "score" => {
+                row.score = Some(
+                    field
+                        .parse()
+                        .map_err(|_| format!("invalid score '{field}'"))?,
+                );
+            }
+            "unit" => row.unit = field,
+            "timestamp" => row.timestamp = field,
+            "duration_ms" if !field.is_empty() => {
+                row.duration_ms = field
+                    .parse()
+                    .map_err(|_| format!("invalid duration '{field}'"))?;
+            }
+            "flag" if !field.is_empty() => {
+                row.flag =
+                    Some(Flag::from_name(&field).ok_or_else(|| format!("unknown flag '{field}'"))?);
+            }
+            // Columns added to exports later, or that only the file's author knows about.
+            _ => (),
+        }
+    }
+    Ok(row)
+}
+
+/// Fields of a CSV line, which may be quoted, with quotes inside doubled.
+fn split(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            // Quotes only open a field, which may follow the comma after some space.
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_owned()),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(String::from("unterminated quote"));
+    }
+    fields.push(field.trim().to_owned());
+    Ok(fields)
+}
+
+/// Turns a row into an attempt of the game it names, at the registry index returned.
+fn check(row: Row) -> Result<(usize, Attempt), String> {
+    let game =
+        registry::position(&row.game).ok_or_else(|| format!("unknown game '{}'", row.game))?;
+    let entry = &registry::GAMES[game];
+
+    if !row.unit.is_empty() && row.unit != entry.unit {
+        return Err(format!(
+            "{} is scored in {}, not {}",
+            entry.name, entry.unit, row.unit
+        ));
+    }
+    let score = row.score.ok_or_else(|| String::from("no score"))?;
+    if !score.is_finite() || score < 0.0 {
+        return Err(format!("invalid score {score}"));
+    }
+    // Without a unit, a score faster than anyone can manage is more likely in another unit, like
+    // a reaction time in seconds, than anticipated.
+    if row.unit.is_empty()
+        && let Some(floor) = entry.rules.floor
+        && score < floor
+    {
+        return Err(format!(
+            "implausible score {score} for {}, which is scored in {}, without a unit",
+            entry.name, entry.unit
+        ));
+    }
+    let timestamp = parse_time(&row.timestamp)
+        .ok_or_else(|| format!("invalid timestamp '{}'", row.timestamp))?;
+    // Scores from elsewhere are held to the rules of attempts played here. A score that's the
+    // mean of a session below the floor had a trial below it too.
+    let flag = row.flag.or_else(|| entry.rules.check(&[score], false));
+
+    Ok((
+        game,
+        Attempt {
+            score,
+            timestamp,
+            variant: if row.variant.is_empty() {
+                String::from("imported")
+            } else {
+                row.variant
+            },
+            duration_ms: row.duration_ms,
+            seed: None,
+            trials: Vec::new(),
+            flag,
+        },
+    ))
+}
+
+/// Adds attempts to a game's save file, leaving out the ones it has already, and keeps the
+/// attempts in the order they were played.
+fn merge(game: usize, entry: &Entry, attempts: Vec<(usize, Attempt)>, report: &mut Report) {
+    let mut savestate = match (entry.read)() {
+        Ok(savestate) => savestate.unwrap_or_default(),
+        Err(e) => {
+            let reason = format!("the save file of {} can't be read: {e}", entry.name);
+            report
+                .skipped
+                .extend(attempts.into_iter().map(|(line, _)| (line, reason.clone())));
+            return;
+        }
+    };
+
+    let mut added = Vec::new();
+    for (line, attempt) in attempts {
+        if savestate.attempts.iter().any(|a| same(a, &attempt)) {
+            report.skipped.push((line, String::from("already saved")));
+            continue;
+        }
+        added.push((line, attempt.flag));
+        savestate.attempts.push(attempt);
+    }
+    if added.is_empty() {
+        return;
+    }
+
+    savestate.attempts.sort_by_key(|attempt| attempt.timestamp);
+    match (entry.write)(&savestate) {
+        Ok(()) => {
+            report.imported[game] = added.len();
+            report.flagged.extend(
+                added
+                    .into_iter()
+                    .filter_map(|(line, flag)| Some((line, flag?))),
+            );
+        }
+        Err(e) => {
+            let reason = format!("could not save {}: {e}", entry.name);
+            report
+                .skipped
+                .extend(added.into_iter().map(|(line, _)| (line, reason.clone())));
+        }
+    }
+}
+
+/// Whether two attempts are the same one, as far as an export can tell.
+fn same(a: &Attempt, b: &Attempt) -> bool {
+    a.timestamp == b.timestamp && a.score == b.score
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::app::{
+        export::{self, Format},
+        savestate::SaveState,
+        storage,
+    };
+
+    fn reaction_time() -> &'static Entry {
+        registry::find("reaction-time").unwrap()
+    }
+
+    fn attempt(score: f32, timestamp: &str, variant: &str) -> Attempt {
+        Attempt {
+            score,
+            timestamp: parse_time(timestamp).unwrap(),
+            variant: variant.to_owned(),
+            duration_ms: 1234,
+            seed: Some(42),
+            trials: Vec::new(),
+            flag: None,
+        }
+    }
+
+    fn saved(entry: &Entry) -> Vec<Attempt> {
+        (entry.read)().unwrap().unwrap_or_default().attempts
+    }
+
+    fn round_trip(format: Format) {
+        let entry = reaction_time();
+        let mut flagged = attempt(95.5, "2026-03-01T09:30:15.250+01:00", "single");
+        flagged.flag = Some(Flag::Anticipation);
+        let savestate = SaveState {
+            attempts: vec![
+                attempt(251.25, "2026-03-01T09:00:00+01:00", "session of 5"),
+                attempt(300.0, "2026-03-01T08:20:00Z", r#"a "quoted", odd variant"#),
+                flagged,
+            ],
+            ..SaveState::default()
+        };
+        (entry.write)(&savestate).unwrap();
+        let text = export::write(
+            &export::records(&[(entry, savestate.clone())], None),
+            format,
+        );
+
+        // What's saved already is left alone.
+        let report = import(&text);
+        assert_eq!(report.total(), 0);
+        assert_eq!(report.skipped.len(), 3);
+        assert!(report.skipped.iter().all(|(_, e)| e == "already saved"));
+
+        storage::remove(&(entry.save_file)().unwrap()).unwrap();
+        let report = import(&text);
+        assert_eq!(report.total(), 3, "{:?}", report.skipped);
+        assert_eq!(report.flagged.len(), 1);
+
+        let imported = saved(entry);
+        assert_eq!(imported.len(), 3);
+        for (a, b) in savestate.attempts.iter().zip(&imported) {
+            assert_eq!(a.score, b.score);
+            assert_eq!(a.timestamp, b.timestamp);
+            assert_eq!(a.variant, b.variant);
+            assert_eq!(a.duration_ms, b.duration_ms);
+            assert_eq!(a.flag, b.flag);
+        }
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        round_trip(Format::Csv);
+    }
+
+    #[test]
+    fn jsonl_round_trip() {
+        round_trip(Format::Jsonl);
+    }
+
+    #[test]
+    fn generic_csv_with_and_without_a_header() {
+        let report = import("reaction-time,250,2026-01-01 10:00\ntyping,80,2026-01-01");
+        assert_eq!(report.total(), 2, "{:?}", report.skipped);
+
+        let report = import("Timestamp,Score,Game\n2026-01-02,260,Reaction Time\n");
+        assert_eq!(report.total(), 1, "{:?}", report.skipped);
+        assert_eq!(saved(reaction_time()).len(), 2);
+        assert_eq!(saved(reaction_time())[1].variant, "imported");
+    }
+
+    #[test]
+    fn bad_rows_are_skipped() {
+        let report = import(
+            "game,score,unit,timestamp\n\
+             reaction-time,250,wpm,2026-01-01\n\
+             chess,1,,2026-01-01\n\
+             reaction-time,fast,,2026-01-01\n\
+             reaction-time,250,,yesterday\n\
+             reaction-time,\"250,,2026-01-01\n",
+        );
+        assert_eq!(report.total(), 0);
+        let lines: Vec<usize> = report.skipped.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, [2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn implausible_scores_dont_count() {
+        let report = import(
+            "game,score,unit,timestamp\n\
+             reaction-time,5,ms,2026-01-01\n\
+             reaction-time,210,ms,2026-01-02",
+        );
+        assert_eq!(report.total(), 2);
+        assert_eq!(report.flagged, [(2, Flag::Anticipation)]);
+
+        let attempts = saved(reaction_time());
+        assert!(!attempts[0].counts());
+        assert!(attempts[1].counts());
+    }
+
+    #[test]
+    fn scores_in_another_unit_need_one() {
+        let report = import("reaction-time,0.25,2026-01-01\nreaction-time,250,2026-01-02");
+        assert_eq!(report.total(), 1);
+        assert_eq!(report.skipped.len(), 1);
+        let (line, reason) = &report.skipped[0];
+        assert_eq!(*line, 1);
+        assert!(reason.starts_with("implausible score 0.25"), "{reason}");
+        assert!(report.flagged.is_empty());
+
+        // Games without a floor take any score.
+        assert_eq!(import("chimp-test,1,2026-01-01").total(), 1);
+    }
+
+    #[test]
+    fn times() {
+        let local = |y, m, d, h, min, s| Local.with_ymd_and_hms(y, m, d, h, min, s).unwrap();
+        assert_eq!(
+            parse_time("2026-01-02T03:04:05Z"),
+            Some(
+                chrono::Utc
+                    .with_ymd_and_hms(2026, 1, 2, 3, 4, 5)
+                    .unwrap()
+                    .into()
+            )
+        );
+        assert_eq!(
+            parse_time("2026-01-02 03:04:05"),
+            Some(local(2026, 1, 2, 3, 4, 5))
+        );
+        assert_eq!(
+            parse_time("2026-01-02 03:04"),
+            Some(local(2026, 1, 2, 3, 4, 0))
+        );
+        assert_eq!(parse_time("2026-01-02"), Some(local(2026, 1, 2, 0, 0, 0)));
+        assert_eq!(parse_time("02/01/2026"), None);
+        assert_eq!(parse_time(""), None);
+    }
+
+    #[test]
+    fn quoted_fields() {
+        assert_eq!(split(r#"a, "b,""c""" ,d"#).unwrap(), ["a", r#"b,"c""#, "d"]);
+        assert!(split(r#"a,"b"#).is_err());
+    }
+}
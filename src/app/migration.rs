@@ -0,0 +1,132 @@
+use serde_json::{Map, Value};
+
+/// Format version written into every save file. Bump it and add a step to [`STEPS`] whenever
+/// files written before the change can't be read as they are.
+pub const VERSION: u64 = 1;
+
+/// Field of a save file that holds its version. Files without one are version 0.
+const FIELD: &str = "version";
+
+type Step = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `STEPS[n]` takes a file from version `n` to version `n + 1`.
+const STEPS: [Step; VERSION as usize] = [v0_to_v1];
+
+/// Brings a save file up to [`VERSION`], one step at a time. Fails on files from a newer version
+/// of the app and on files that aren't save files at all, which have to be kept as they are.
+pub fn upgrade(value: Value) -> Result<Value, String> {
+    let Value::Object(mut fields) = value else {
+        return Err(String::from("not a save file"));
+    };
+
+    let version = match fields.get(FIELD) {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format!("invalid version {version}"))?,
+    };
+    if version > VERSION {
+        return Err(format!(
+            "written by a newer version of the app (format {version}, this one reads up to {VERSION})"
+        ));
+    }
+
+    for (from, step) in STEPS.iter().enumerate().skip(version as usize) {
+        step(&mut fields).map_err(|e| format!("upgrading from format {from}: {e}"))?;
+    }
+    fields.insert(String::from(FIELD), Value::from(VERSION));
+    Ok(Value::Object(fields))
+}
+
+/// Marks a save state about to be written with the current version.
+pub fn stamp(value: Value) -> Value {
+    match value {
+        Value::Object(mut fields) => {
+            fields.insert(String::from(FIELD), Value::from(VERSION));
+            Value::Object(fields)
+        }
+        value => value,
+    }
+}
+
+/// Files from before versioning hold either just the aggregate of every result, as
+/// `avg_score` and `num_entries`, or that aggregate renamed next to a list of attempts.
+fn v0_to_v1(fields: &mut Map<String, Value>) -> Result<(), String> {
+    for (old, new) in [
+        ("avg_score", "legacy_avg"),
+        ("num_entries", "legacy_entries"),
+    ] {
+        if let Some(value) = fields.remove(old) {
+            fields.insert(String::from(new), value);
+        }
+    }
+    for field in ["legacy_avg", "legacy_entries"] {
+        if !fields.get(field).is_some_and(Value::is_number) {
+            return Err(format!("missing `{field}`"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::app::savestate::SaveState;
+
+    fn read(value: Value) -> Result<SaveState, String> {
+        serde_json::from_value(upgrade(value)?).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn file_from_before_attempts() {
+        let savestate = read(json!({ "avg_score": 250.5, "num_entries": 4 })).unwrap();
+        assert_eq!(savestate.legacy_avg, 250.5);
+        assert_eq!(savestate.legacy_entries, 4);
+        assert!(savestate.attempts.is_empty());
+    }
+
+    #[test]
+    fn file_with_attempts_and_no_version() {
+        let savestate = read(json!({
+            "legacy_avg": 300.0,
+            "legacy_entries": 2,
+            "attempts": [{ "score": 280.0, "timestamp": "2025-06-01T12:00:00+02:00" }],
+        }))
+        .unwrap();
+        assert_eq!(savestate.legacy_avg, 300.0);
+        assert_eq!(savestate.legacy_entries, 2);
+        assert_eq!(savestate.attempts.len(), 1);
+        assert_eq!(savestate.attempts[0].score, 280.0);
+        assert_eq!(savestate.attempts[0].variant, "");
+    }
+
+    #[test]
+    fn upgraded_files_get_the_current_version() {
+        let value = upgrade(json!({ "avg_score": 1.0, "num_entries": 1 })).unwrap();
+        assert_eq!(value[FIELD], VERSION);
+        assert_eq!(upgrade(value.clone()), Ok(value));
+    }
+
+    #[test]
+    fn file_from_a_newer_version() {
+        let error = upgrade(json!({
+            "version": VERSION + 1,
+            "legacy_avg": 0.0,
+            "legacy_entries": 0,
+        }))
+        .unwrap_err();
+        assert!(error.contains("newer version"), "{error}");
+    }
+
+    #[test]
+    fn file_that_is_not_a_save_file() {
+        assert_eq!(
+            upgrade(json!([1, 2, 3])),
+            Err(String::from("not a save file"))
+        );
+        assert_eq!(upgrade(json!("text")), Err(String::from("not a save file")));
+        assert!(upgrade(json!({ "score": 1 })).is_err());
+    }
+}
@@ -3,29 +3,39 @@ mod number;
 
 use std::{
     cmp::Ordering,
+    sync::LazyLock,
     time::{Duration, Instant},
 };
 
 use mode::Mode;
 use number::Number;
-use rand::{Rng, rng};
+use rand::Rng;
 use ratatui::{
-    Frame,
+    Frame, Terminal,
+    backend::Backend,
     crossterm::event::{self, KeyCode},
     layout::{Constraint, Direction, Layout, Margin},
     style::{Color, Stylize},
-    symbols::{Marker, border},
+    symbols::border,
     text::Span,
-    widgets::{Block, Dataset, GraphType, Paragraph, Widget},
+    widgets::{Block, Paragraph, Widget},
 };
 
-use super::{Filed, Game, render_graph, savestate::SaveState};
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, population::Population,
+    render_results, rng::SessionRng, savestate::SaveState,
+};
 
 const FILE_NAME: &str = "NumberMemory";
+const VARIANT: &str = "standard";
 const FADE_OUT: u64 = 2000;
 const ADDED_FADE: u64 = 600;
 
-#[derive(Default, Debug, Clone)]
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<NumberMemory>(include_str!("../../data/population/NumberMemory.csv"))
+});
+
+#[derive(Debug, Clone)]
 pub struct NumberMemory {
     exit: bool,
     mode: Mode,
@@ -33,13 +43,36 @@ pub struct NumberMemory {
 
     number: Number,
     actual_number: Number,
+    start: Option<Instant>,
     savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
+}
+
+impl Default for NumberMemory {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            mode: Mode::default(),
+            score: 0,
+            number: Number::default(),
+            actual_number: Number::default(),
+            start: None,
+            savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng: SessionRng::attempt(FILE_NAME),
+            view: ResultsView::default(),
+        }
+    }
 }
 
 impl NumberMemory {
     fn reset(&mut self) {
         let new = Self {
-            savestate: self.savestate,
+            savestate: std::mem::take(&mut self.savestate),
+            clock: self.clock.clone(),
+            view: self.view,
 
             ..Default::default()
         };
@@ -58,7 +91,7 @@ impl NumberMemory {
     fn new_number(&mut self) {
         self.score += 1;
 
-        let mut rng = rng();
+        let rng = &mut self.rng;
 
         self.number.clear();
         self.actual_number.clear();
@@ -78,10 +111,17 @@ impl NumberMemory {
         }
         if self.number == self.actual_number {
             self.new_number();
-            self.mode = Mode::Watching(Instant::now());
+            self.mode = Mode::Watching(self.clock.now());
         } else {
             self.mode = Mode::Results;
-            self.savestate.update(self.score as f32);
+            self.savestate.update(
+                self.score as f32,
+                VARIANT,
+                self.start
+                    .map(|s| self.clock.elapsed(s))
+                    .unwrap_or_default(),
+                self.rng.seed(),
+            );
         }
     }
 
@@ -91,78 +131,88 @@ impl NumberMemory {
 }
 
 impl Game for NumberMemory {
-    fn run(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
+    fn run<B: Backend>(
+        terminal: &mut Terminal<B>,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<()> {
         let mut game = Self::load().unwrap_or_default();
+        game.clock = events.clock();
 
         while !game.exit {
             terminal.draw(|frame| game.draw(frame))?;
-            game.handle_input(terminal)?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
         }
 
         game.save();
         Ok(())
     }
 
-    fn handle_input(&mut self, _: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> std::io::Result<()> {
         match self.mode {
             Mode::Waiting => {
-                if event::poll(Duration::MAX)? {
-                    if let event::Event::Key(key) = event::read()? {
-                        match key.code {
-                            KeyCode::Char(' ') | KeyCode::Enter => {
-                                self.mode = Mode::Watching(Instant::now());
-                                self.new_number();
-                            }
-                            KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
-                            KeyCode::Char('r') => self.reset(),
-                            _ => (),
+                if events.poll(Duration::MAX)?
+                    && let event::Event::Key(key) = events.read()?
+                {
+                    match key.code {
+                        KeyCode::Char(' ') | KeyCode::Enter => {
+                            self.mode = Mode::Watching(self.clock.now());
+                            self.start = Some(self.clock.now());
+                            self.new_number();
                         }
+                        KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+                        KeyCode::Char('r') => self.reset(),
+                        _ => (),
                     }
                 }
             }
             Mode::Watching(instant) => {
-                if instant.elapsed().as_millis() as u64 >= self.get_dur() {
+                if self.clock.elapsed(instant).as_millis() as u64 >= self.get_dur() {
                     self.mode = Mode::Playing;
                 }
-                if event::poll(Duration::from_millis(self.get_dur() / 10))? {
-                    if let event::Event::Key(key) = event::read()? {
-                        match key.code {
-                            KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
-                            KeyCode::Char('r') => self.reset(),
-                            KeyCode::Char(' ') => self.mode = Mode::Playing,
-                            _ => (),
-                        }
+                if events.poll(Duration::from_millis(self.get_dur() / 10))?
+                    && let event::Event::Key(key) = events.read()?
+                {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+                        KeyCode::Char('r') => self.reset(),
+                        KeyCode::Char(' ') => self.mode = Mode::Playing,
+                        _ => (),
                     }
                 }
             }
             Mode::Playing => {
-                if event::poll(Duration::MAX)? {
-                    if let event::Event::Key(key) = event::read()? {
-                        match key.code {
-                            KeyCode::Esc => self.exit = true,
-                            KeyCode::Char(ch) => match ch {
-                                'q' => self.exit = true,
-                                'r' => self.reset(),
-                                '0'..='9' => self.add_ch(ch),
-                                _ => (),
-                            },
-                            KeyCode::Backspace => {
-                                let _ = self.number.pop();
-                            }
-                            KeyCode::Enter => self.process_number(),
+                if events.poll(Duration::MAX)?
+                    && let event::Event::Key(key) = events.read()?
+                {
+                    match key.code {
+                        KeyCode::Esc => self.exit = true,
+                        KeyCode::Char(ch) => match ch {
+                            'q' => self.exit = true,
+                            'r' => self.reset(),
+                            '0'..='9' => self.add_ch(ch),
                             _ => (),
+                        },
+                        KeyCode::Backspace => {
+                            let _ = self.number.pop();
                         }
+                        KeyCode::Enter => self.process_number(),
+                        _ => (),
                     }
                 }
             }
             Mode::Results => {
-                if event::poll(Duration::MAX)? {
-                    if let event::Event::Key(key) = event::read()? {
-                        match key.code {
-                            KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
-                            KeyCode::Enter | KeyCode::Char('r') => self.reset(),
-                            _ => (),
-                        }
+                if events.poll(Duration::MAX)?
+                    && let event::Event::Key(key) = events.read()?
+                {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+                        KeyCode::Enter | KeyCode::Char('r') => self.reset(),
+                        KeyCode::Tab => self.view.tab.toggle(),
+                        KeyCode::Char('h') => self.view.toggle_histogram(),
+                        _ => (),
                     }
                 }
             }
@@ -171,8 +221,8 @@ impl Game for NumberMemory {
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        frame.render_widget(&*self, frame.area());
     }
 }
 
@@ -181,7 +231,7 @@ impl Filed<'_> for NumberMemory {
     const NAME: &'static str = FILE_NAME;
 
     fn get_savestate(&self) -> Self::SaveState {
-        self.savestate
+        self.savestate.clone()
     }
     fn from_savestate(savestate: Self::SaveState) -> Self {
         Self {
@@ -234,7 +284,8 @@ impl Widget for &NumberMemory {
                 block.clone().title("╡ Watching.. ╞").render(vert[1], buf);
 
                 let mut string = String::from("╡");
-                let percent = ((instant.elapsed().as_millis() as f32 / (self.get_dur()) as f32)
+                let percent = ((self.clock.elapsed(instant).as_millis() as f32
+                    / (self.get_dur()) as f32)
                     * 10.0)
                     .round();
                 for i in 1..=10 {
@@ -318,42 +369,11 @@ impl Widget for &NumberMemory {
             Mode::Results => {
                 block.title("╡ Results ╞").render(vert[1], buf);
 
-                let dataset = Dataset::default()
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .cyan()
-                    .data(&[
-                        (0.0, (0.0 / 280.0)),
-                        (1.0, (18.0 / 280.0)),
-                        (2.0, (5.0 / 280.0)),
-                        (3.0, (5.0 / 280.0)),
-                        (4.0, (7.0 / 280.0)),
-                        (5.0, (15.0 / 280.0)),
-                        (6.0, (42.0 / 280.0)),
-                        (7.0, (112.0 / 280.0)),
-                        (8.0, (206.0 / 280.0)),
-                        (9.0, (255.0 / 280.0)),
-                        (10.0, (200.0 / 280.0)),
-                        (11.0, (135.0 / 280.0)),
-                        (12.0, (65.0 / 280.0)),
-                        (13.0, (35.0 / 280.0)),
-                        (14.0, (20.0 / 280.0)),
-                        (15.0, (10.0 / 280.0)),
-                        (16.0, (7.0 / 280.0)),
-                        (17.0, (5.0 / 280.0)),
-                        (18.0, (4.0 / 280.0)),
-                        (19.0, (3.0 / 280.0)),
-                        (20.0, (2.0 / 280.0)),
-                        (21.0, (1.0 / 280.0)),
-                        (22.0, (0.0 / 280.0)),
-                        (23.0, (0.0 / 280.0)),
-                    ]);
-
-                render_graph(
-                    self.savestate.avg_score as f64,
+                render_results(
+                    self.view,
                     self.score as f64,
-                    dataset,
-                    [0.0, 23.0],
+                    &self.savestate,
+                    &POPULATION,
                     main,
                     buf,
                 );
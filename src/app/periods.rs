@@ -0,0 +1,172 @@
+use chrono::{Datelike, Timelike, Weekday};
+use serde::Serialize;
+
+use super::savestate::Attempt;
+
+/// How attempts get grouped by when they were played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// Hour of the day, in local time.
+    Hour,
+    Weekday,
+}
+
+impl Period {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hour" => Some(Period::Hour),
+            "weekday" => Some(Period::Weekday),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Period::Hour => "hour",
+            Period::Weekday => "weekday",
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        *self = match self {
+            Period::Hour => Period::Weekday,
+            Period::Weekday => Period::Hour,
+        };
+    }
+
+    fn len(self) -> usize {
+        match self {
+            Period::Hour => 24,
+            Period::Weekday => 7,
+        }
+    }
+
+    fn index(self, attempt: &Attempt) -> usize {
+        match self {
+            Period::Hour => attempt.timestamp.hour() as usize,
+            Period::Weekday => attempt.timestamp.weekday().num_days_from_monday() as usize,
+        }
+    }
+
+    fn label(self, index: usize) -> String {
+        match self {
+            Period::Hour => format!("{index:02}"),
+            Period::Weekday => Weekday::try_from(index as u8)
+                .map(|day| day.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Attempts played in one hour of the day or on one day of the week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bucket {
+    pub label: String,
+    pub count: usize,
+    /// Mean score, zero if nothing was played in this bucket.
+    pub mean: f32,
+}
+
+/// Groups the attempts that count by `period`, keeping empty buckets so that every hour or day
+/// is listed.
+pub fn buckets(attempts: &[Attempt], period: Period) -> Vec<Bucket> {
+    let mut sums = vec![(0usize, 0.0f64); period.len()];
+    for attempt in attempts.iter().filter(|a| a.counts()) {
+        let (count, sum) = &mut sums[period.index(attempt)];
+        *count += 1;
+        *sum += attempt.score as f64;
+    }
+
+    sums.into_iter()
+        .enumerate()
+        .map(|(i, (count, sum))| Bucket {
+            label: period.label(i),
+            count,
+            mean: if count > 0 {
+                (sum / count as f64) as f32
+            } else {
+                0.0
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Local, TimeZone, Utc};
+
+    use super::*;
+    use crate::app::plausibility::Flag;
+
+    fn attempt(score: f32, timestamp: DateTime<Local>) -> Attempt {
+        Attempt {
+            score,
+            timestamp,
+            variant: String::new(),
+            duration_ms: 0,
+            seed: None,
+            trials: Vec::new(),
+            flag: None,
+        }
+    }
+
+    fn local(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, day, hour, 15, 0).unwrap()
+    }
+
+    #[test]
+    fn attempts_fall_into_their_hour() {
+        let mut flagged = attempt(1000.0, local(2, 9));
+        flagged.flag = Some(Flag::Anticipation);
+        let attempts = [
+            attempt(200.0, local(2, 9)),
+            attempt(300.0, local(3, 9)),
+            attempt(250.0, local(4, 21)),
+            flagged,
+        ];
+
+        let buckets = buckets(&attempts, Period::Hour);
+        assert_eq!(buckets.len(), 24);
+        assert_eq!(buckets[9].label, "09");
+        assert_eq!((buckets[9].count, buckets[9].mean), (2, 250.0));
+        assert_eq!((buckets[21].count, buckets[21].mean), (1, 250.0));
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<usize>(), 3);
+        assert_eq!((buckets[0].count, buckets[0].mean), (0, 0.0));
+    }
+
+    #[test]
+    fn attempts_fall_into_their_weekday() {
+        // 2 March 2026 is a Monday.
+        let attempts = [
+            attempt(10.0, local(2, 8)),
+            attempt(20.0, local(9, 8)),
+            attempt(5.0, local(8, 8)),
+        ];
+
+        let buckets = buckets(&attempts, Period::Weekday);
+        assert_eq!(buckets.len(), 7);
+        assert_eq!(buckets[0].label, "Mon");
+        assert_eq!((buckets[0].count, buckets[0].mean), (2, 15.0));
+        assert_eq!(buckets[6].label, "Sun");
+        assert_eq!((buckets[6].count, buckets[6].mean), (1, 5.0));
+        assert_eq!(buckets[1..6].iter().map(|b| b.count).sum::<usize>(), 0);
+    }
+
+    #[test]
+    fn hours_are_local_time() {
+        let utc = Utc.with_ymd_and_hms(2026, 3, 2, 23, 30, 0).unwrap();
+        let played = utc.with_timezone(&Local);
+        let hours = buckets(&[attempt(1.0, played)], Period::Hour);
+
+        assert_eq!(hours[played.hour() as usize].count, 1);
+        // Wherever the local hour differs, nothing is counted at 23, the hour in UTC.
+        if played.hour() != 23 {
+            assert_eq!(hours[23].count, 0);
+        }
+        let weekday = buckets(&[attempt(1.0, played)], Period::Weekday);
+        assert_eq!(
+            weekday[played.weekday().num_days_from_monday() as usize].count,
+            1
+        );
+    }
+}
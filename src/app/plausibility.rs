@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+/// Why an attempt can't have been played fairly by a person. Flagged attempts stay in the
+/// history but don't count towards averages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Flag {
+    /// A trial was faster than people can react, so the input came before the stimulus.
+    Anticipation,
+    /// Trials were timed too evenly for a person, like a macro would.
+    Regularity,
+    /// The input was a key held down and repeating rather than a press.
+    KeyRepeat,
+}
+
+impl Flag {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "anticipation" => Some(Flag::Anticipation),
+            "regularity" => Some(Flag::Regularity),
+            "key-repeat" => Some(Flag::KeyRepeat),
+            _ => None,
+        }
+    }
+
+    /// Name of the flag in save files and exports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Flag::Anticipation => "anticipation",
+            Flag::Regularity => "regularity",
+            Flag::KeyRepeat => "key-repeat",
+        }
+    }
+
+    pub fn reason(self) -> &'static str {
+        match self {
+            Flag::Anticipation => "a trial was faster than anyone can react",
+            Flag::Regularity => "the trials were too evenly timed to be human",
+            Flag::KeyRepeat => "a held key repeated the input",
+        }
+    }
+}
+
+/// Plausibility rules of a game, applied to the individual trial times of an attempt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rules {
+    /// Fastest trial in milliseconds a person can manage.
+    pub floor: Option<f32>,
+    /// Smallest spread of trial times, as a fraction of their mean, that a person shows over
+    /// [`Rules::REGULARITY_TRIALS`] trials or more.
+    pub min_spread: Option<f32>,
+}
+
+impl Rules {
+    /// No checks, for games scored on levels rather than times.
+    pub const NONE: Rules = Rules {
+        floor: None,
+        min_spread: None,
+    };
+    /// Fewest trials the spread is judged on.
+    pub const REGULARITY_TRIALS: usize = 5;
+
+    /// First rule the trials break, if any. `repeated` tells whether a scoring input was a
+    /// repeating key.
+    pub fn check(&self, trials: &[f32], repeated: bool) -> Option<Flag> {
+        if repeated {
+            return Some(Flag::KeyRepeat);
+        }
+        if let Some(floor) = self.floor
+            && trials.iter().any(|&t| t < floor)
+        {
+            return Some(Flag::Anticipation);
+        }
+        if let Some(min_spread) = self.min_spread
+            && trials.len() >= Rules::REGULARITY_TRIALS
+        {
+            let n = trials.len() as f32;
+            let mean = trials.iter().sum::<f32>() / n;
+            let variance = trials.iter().map(|t| (t - mean).powi(2)).sum::<f32>() / (n - 1.0);
+            if mean > 0.0 && variance.sqrt() / mean < min_spread {
+                return Some(Flag::Regularity);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOOR: Rules = Rules {
+        floor: Some(100.0),
+        min_spread: None,
+    };
+
+    #[test]
+    fn floor() {
+        assert_eq!(
+            FLOOR.check(&[250.0, 99.9, 300.0], false),
+            Some(Flag::Anticipation)
+        );
+        assert_eq!(FLOOR.check(&[250.0, 100.0, 300.0], false), None);
+        assert_eq!(Rules::NONE.check(&[1.0], false), None);
+        assert_eq!(FLOOR.check(&[250.0], true), Some(Flag::KeyRepeat));
+    }
+
+    #[test]
+    fn regularity_uses_the_sample_deviation() {
+        // Mean 102, deviation 4.47 over n - 1 but 4 over n, so 4.4% and 3.9% of the mean.
+        let trials = [100.0, 100.0, 100.0, 100.0, 110.0];
+        let rules = |min_spread| Rules {
+            floor: None,
+            min_spread: Some(min_spread),
+        };
+        assert_eq!(rules(0.041).check(&trials, false), None);
+        assert_eq!(rules(0.045).check(&trials, false), Some(Flag::Regularity));
+    }
+
+    #[test]
+    fn regularity_needs_enough_trials() {
+        let rules = Rules {
+            floor: None,
+            min_spread: Some(0.02),
+        };
+        let trials = [200.0; Rules::REGULARITY_TRIALS];
+        assert_eq!(rules.check(&trials[1..], false), None);
+        assert_eq!(rules.check(&trials, false), Some(Flag::Regularity));
+    }
+}
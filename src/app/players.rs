@@ -0,0 +1,618 @@
+mod mode;
+
+use std::{
+    cmp::Ordering,
+    fs, io,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use mode::Mode;
+use ratatui::{
+    Frame, Terminal,
+    backend::Backend,
+    crossterm::event::{self, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style, Styled, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Row, Table, Widget},
+};
+use serde::Serialize;
+
+use super::{Game, events::EventSource, regions::Regions, registry};
+
+/// Directory under the data directory that holds one directory of save files per player.
+const DIR: &str = "profiles";
+/// Player of anyone who never picked one, including everyone from before there were players.
+pub const DEFAULT: &str = "default";
+/// File next to [`DIR`] that remembers who was picked last.
+const ACTIVE_FILE: &str = "active_profile";
+const MAX_NAME: usize = 24;
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Player picked for this run, with `--player` or on the player screen.
+static ACTIVE: Mutex<Option<String>> = Mutex::new(None);
+
+fn root() -> Option<PathBuf> {
+    super::data_dir()
+}
+
+/// Directory of the save files of `player`.
+pub fn dir(player: &str) -> Option<PathBuf> {
+    Some(root()?.join(DIR).join(player))
+}
+
+/// Player whose results are read and saved: the one picked for this run, else the one picked
+/// last time, else the default one.
+pub fn active() -> String {
+    if let Some(player) = ACTIVE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+    {
+        return player;
+    }
+    root()
+        .and_then(|root| fs::read_to_string(root.join(ACTIVE_FILE)).ok())
+        .map(|player| player.trim().to_owned())
+        .filter(|player| exists(player))
+        .unwrap_or_else(|| String::from(DEFAULT))
+}
+
+/// Whether a player was picked for this run already.
+pub fn picked() -> bool {
+    ACTIVE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
+}
+
+/// Plays as `player` for the rest of this run.
+pub fn set_active(player: &str) {
+    *ACTIVE.lock().unwrap_or_else(PoisonError::into_inner) = Some(player.to_owned());
+}
+
+/// Plays as `player` from now on, in later runs too.
+pub fn switch(player: &str) -> io::Result<()> {
+    set_active(player);
+    let root = root().ok_or_else(|| io::Error::other("could not find the data directory"))?;
+    fs::create_dir_all(&root)?;
+    fs::write(root.join(ACTIVE_FILE), player)
+}
+
+/// Whether `player` is one of the [`list`]. The default player is there before they have a
+/// directory.
+pub fn exists(player: &str) -> bool {
+    player == DEFAULT || dir(player).is_some_and(|dir| dir.is_dir())
+}
+
+/// Every player, by name, with the one playing among them even before they've saved anything.
+pub fn list() -> Vec<String> {
+    let mut players: Vec<String> = root()
+        .and_then(|root| fs::read_dir(root.join(DIR)).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    let active = active();
+    if !players.contains(&active) {
+        players.push(active);
+    }
+    players.sort();
+    players
+}
+
+/// Checks that `name` can be used as a player's name, which is also a directory name.
+pub fn validate(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(String::from("a name can't be empty"));
+    }
+    if name.trim() != name {
+        return Err(String::from("a name can't start or end with a space"));
+    }
+    if name.chars().count() > MAX_NAME {
+        return Err(format!("a name has at most {MAX_NAME} characters"));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+    {
+        return Err(String::from(
+            "a name has only letters, digits, spaces, '-' and '_'",
+        ));
+    }
+    Ok(())
+}
+
+fn new_dir(name: &str) -> Result<PathBuf, String> {
+    validate(name)?;
+    if exists(name) {
+        return Err(format!("there is already a player called {name}"));
+    }
+    dir(name).ok_or_else(|| String::from("could not find the data directory"))
+}
+
+pub fn create(name: &str) -> Result<(), String> {
+    let dir = new_dir(name)?;
+    fs::create_dir_all(dir).map_err(|e| e.to_string())
+}
+
+/// Renames `player` along with their results, and keeps playing as them if they were playing.
+pub fn rename(player: &str, name: &str) -> Result<(), String> {
+    let to = new_dir(name)?;
+    let from = dir(player).ok_or_else(|| String::from("could not find the data directory"))?;
+    if from.is_dir() {
+        fs::rename(from, to).map_err(|e| e.to_string())?;
+    } else {
+        fs::create_dir_all(to).map_err(|e| e.to_string())?;
+    }
+    if active() == player {
+        switch(name).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Deletes `player` and every result they've saved. The player who is playing can't be deleted.
+pub fn delete(player: &str) -> Result<(), String> {
+    if active() == player {
+        return Err(String::from(
+            "switch to someone else before deleting this player",
+        ));
+    }
+    match dir(player) {
+        Some(dir) if dir.is_dir() => fs::remove_dir_all(dir).map_err(|e| e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Moves save files from before there were players into the default player's directory. Does
+/// nothing once the players' directory exists.
+pub fn migrate() -> io::Result<()> {
+    let Some(root) = root() else {
+        return Ok(());
+    };
+    let players = root.join(DIR);
+    if players.exists() || !root.is_dir() {
+        return Ok(());
+    }
+
+    let default = players.join(DEFAULT);
+    fs::create_dir_all(&default)?;
+    for entry in fs::read_dir(&root)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        // Save files and their backups, as opposed to the log and the population curves.
+        let save = name.ends_with(".json") || name.contains(".json.");
+        if save && entry.path().is_file() {
+            fs::rename(entry.path(), default.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// How one player does in one game.
+#[derive(Debug, Clone, Serialize)]
+pub struct Standing {
+    pub player: String,
+    pub attempts: u32,
+    pub average: f32,
+    pub best: Option<f32>,
+    /// Percentile of the average on the population curve.
+    pub percentile: f64,
+}
+
+/// Every player who has played `entry`, best average first. Save files that can't be read are
+/// left out.
+pub fn leaderboard(entry: &registry::Entry) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = list()
+        .into_iter()
+        .filter_map(|player| {
+            let savestate = (entry.read_player)(&player).ok()??;
+            (savestate.num_entries() > 0).then(|| Standing {
+                attempts: savestate.num_entries(),
+                average: savestate.avg_score(),
+                best: savestate.stats(entry.better).map(|stats| stats.best),
+                percentile: entry.population.percentile(savestate.avg_score() as f64),
+                player,
+            })
+        })
+        .collect();
+    standings.sort_by(|a, b| {
+        if entry.better.is_better(a.average, b.average) {
+            Ordering::Less
+        } else if entry.better.is_better(b.average, a.average) {
+            Ordering::Greater
+        } else {
+            a.player.cmp(&b.player)
+        }
+    });
+    standings
+}
+
+/// A player as listed on the player screen.
+struct Player {
+    name: String,
+    /// Attempts that count, over every game.
+    attempts: u32,
+    games: usize,
+    last_played: Option<DateTime<Local>>,
+}
+
+impl Player {
+    fn load(name: String) -> Self {
+        let savestates: Vec<_> = registry::GAMES
+            .iter()
+            .filter_map(|entry| (entry.read_player)(&name).ok().flatten())
+            .filter(|savestate| savestate.num_entries() > 0)
+            .collect();
+        Self {
+            attempts: savestates.iter().map(|s| s.num_entries()).sum(),
+            games: savestates.len(),
+            last_played: savestates
+                .iter()
+                .filter_map(|s| s.attempts.last())
+                .map(|attempt| attempt.timestamp)
+                .max(),
+            name,
+        }
+    }
+}
+
+/// Picks who plays, manages players and compares them.
+#[derive(Default)]
+pub struct Players {
+    exit: bool,
+    mode: Mode,
+    players: Vec<Player>,
+    selected: usize,
+    /// Outcome of the last change, or why it failed.
+    status: Option<String>,
+    /// Rows of the list, by index into `players`.
+    regions: Regions<usize>,
+    /// Standings in the game on the leaderboard, read when it's picked rather than every frame.
+    standings: Vec<Standing>,
+}
+
+impl Players {
+    fn reload(&mut self) {
+        self.players = list().into_iter().map(Player::load).collect();
+        let active = active();
+        if let Some(i) = self.players.iter().position(|p| p.name == active) {
+            self.selected = i;
+        }
+    }
+
+    fn selected(&self) -> &str {
+        &self.players[self.selected].name
+    }
+
+    fn pick(&mut self, i: usize) {
+        self.selected = i;
+        let player = self.selected().to_owned();
+        match switch(&player) {
+            Ok(()) => self.exit = true,
+            Err(e) => self.status = Some(format!("Could not switch to {player}: {e}")),
+        }
+    }
+
+    fn key_event(&mut self, key: KeyEvent) {
+        match &mut self.mode {
+            Mode::List => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+                KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected = (self.selected + 1).min(self.players.len() - 1);
+                }
+                KeyCode::Enter => self.pick(self.selected),
+                KeyCode::Char('n') => self.prompt_for(Mode::Create(String::new())),
+                KeyCode::Char('r') => self.prompt_for(Mode::Rename(self.selected().to_owned())),
+                KeyCode::Char('d') if self.selected() == active() => {
+                    self.status = Some(String::from(
+                        "Switch to someone else before deleting this player",
+                    ));
+                }
+                KeyCode::Char('d') => self.prompt_for(Mode::Delete),
+                KeyCode::Char('l') => self.show_leaderboard(0),
+                _ => (),
+            },
+            Mode::Create(name) | Mode::Rename(name) => match key.code {
+                KeyCode::Esc => self.mode = Mode::List,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) if name.chars().count() < MAX_NAME => name.push(c),
+                KeyCode::Enter => self.submit(),
+                _ => (),
+            },
+            Mode::Delete => {
+                if let KeyCode::Char('y') = key.code {
+                    let player = self.selected().to_owned();
+                    self.status = Some(match delete(&player) {
+                        Ok(()) => format!("Deleted {player}"),
+                        Err(e) => format!("Could not delete {player}: {e}"),
+                    });
+                    self.reload();
+                }
+                self.mode = Mode::List;
+            }
+            &mut Mode::Leaderboard(game) => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => self.mode = Mode::List,
+                KeyCode::Right | KeyCode::Tab | KeyCode::Char('l') => {
+                    self.show_leaderboard((game + 1) % registry::GAMES.len());
+                }
+                KeyCode::Left | KeyCode::BackTab | KeyCode::Char('h') => {
+                    self.show_leaderboard(
+                        (game + registry::GAMES.len() - 1) % registry::GAMES.len(),
+                    );
+                }
+                _ => (),
+            },
+        }
+    }
+
+    fn prompt_for(&mut self, mode: Mode) {
+        self.status = None;
+        self.mode = mode;
+    }
+
+    fn show_leaderboard(&mut self, game: usize) {
+        self.prompt_for(Mode::Leaderboard(game));
+        self.standings = leaderboard(&registry::GAMES[game]);
+    }
+
+    /// Creates or renames a player with the name typed in, staying on the prompt if it can't be
+    /// used.
+    fn submit(&mut self) {
+        let result = match &self.mode {
+            Mode::Create(name) => create(name).map(|()| format!("Created {name}")),
+            Mode::Rename(name) => {
+                rename(self.selected(), name).map(|()| format!("Renamed to {name}"))
+            }
+            _ => return,
+        };
+        match result {
+            Ok(status) => {
+                self.status = Some(status);
+                self.mode = Mode::List;
+                self.reload();
+            }
+            Err(e) => self.status = Some(e),
+        }
+    }
+
+    fn mouse_event(&mut self, mouse: MouseEvent) {
+        if self.mode == Mode::List
+            && let MouseEventKind::Down(event::MouseButton::Left) = mouse.kind
+            && let Some(i) = self.regions.at(mouse.column, mouse.row)
+        {
+            self.pick(i);
+        }
+    }
+
+    /// Lays out the rows of the list, which are the only clickable part.
+    fn layout(&self, area: Rect) -> Regions<usize> {
+        if matches!(self.mode, Mode::Leaderboard(_)) {
+            return Regions::default();
+        }
+
+        let table = Players::body(area)[0].inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+        // The header takes the first line.
+        (0..self.players.len())
+            .map(|i| {
+                (
+                    i,
+                    Rect::new(table.x, table.y + 1 + i as u16, table.width, 1),
+                )
+            })
+            .filter(|(_, rect)| table.contains(rect.as_position()))
+            .collect()
+    }
+
+    /// Area inside the outer block, below the title, split into the table and the line under it.
+    fn body(area: Rect) -> [Rect; 2] {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(vert[1].inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        }))
+    }
+
+    fn render_list(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let active = active();
+        let rows = self.players.iter().enumerate().map(|(i, player)| {
+            let row = Row::new([
+                if player.name == active { "▶" } else { "" }.to_owned(),
+                player.name.clone(),
+                player.games.to_string(),
+                player.attempts.to_string(),
+                player.last_played.map_or_else(
+                    || String::from("-"),
+                    |played| played.format(DATE_FORMAT).to_string(),
+                ),
+            ]);
+            if i == self.selected {
+                row.set_style(Color::LightRed)
+            } else {
+                row
+            }
+        });
+
+        Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Length(MAX_NAME as u16),
+                Constraint::Length(6),
+                Constraint::Length(9),
+                Constraint::Min(16),
+            ],
+        )
+        .header(Row::new(["", "Player", "Games", "Attempts", "Last played"]).bold())
+        .block(Block::bordered().border_set(border::ROUNDED))
+        .render(area, buf);
+    }
+
+    fn render_leaderboard(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        if self.standings.is_empty() {
+            Paragraph::new("Nobody has played this yet")
+                .dark_gray()
+                .centered()
+                .block(Block::bordered().border_set(border::ROUNDED))
+                .render(area, buf);
+            return;
+        }
+
+        let active = active();
+        let rows = self.standings.iter().enumerate().map(|(i, standing)| {
+            let row = Row::new([
+                format!("{}.", i + 1),
+                standing.player.clone(),
+                standing.attempts.to_string(),
+                format!("{:.1}", standing.average),
+                standing
+                    .best
+                    .map_or_else(|| String::from("-"), |best| format!("{best:.1}")),
+                format!("{:.0}%", standing.percentile),
+            ]);
+            if standing.player == active {
+                row.set_style(Color::LightRed)
+            } else {
+                row
+            }
+        });
+
+        Table::new(
+            rows,
+            [
+                Constraint::Length(4),
+                Constraint::Length(MAX_NAME as u16),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Min(11),
+            ],
+        )
+        .header(Row::new(["", "Player", "Attempts", "Average", "Best", "Percentile"]).bold())
+        .block(Block::bordered().border_set(border::ROUNDED))
+        .render(area, buf);
+    }
+
+    /// The line under the table: what's being typed in and why it can't be used, the question
+    /// being asked, or the outcome of the last change.
+    fn prompt(&self) -> Line<'_> {
+        let status = || {
+            self.status
+                .as_deref()
+                .map(|status| Span::from(status).italic())
+                .unwrap_or_default()
+        };
+        match &self.mode {
+            Mode::Create(name) => Line::from(vec![
+                Span::from(format!("New player: {name}_  ")),
+                status().red(),
+            ]),
+            Mode::Rename(name) => Line::from(vec![
+                Span::from(format!("Rename {} to: {name}_  ", self.selected())),
+                status().red(),
+            ]),
+            Mode::Delete => Line::from(format!(
+                "Delete {} and all their results? y/N",
+                self.selected()
+            ))
+            .red(),
+            Mode::List | Mode::Leaderboard(_) => Line::from(status().dark_gray()),
+        }
+    }
+}
+
+impl Game for Players {
+    fn run<B: Backend>(terminal: &mut Terminal<B>, events: &mut dyn EventSource) -> io::Result<()> {
+        let mut players = Players::default();
+        players.reload();
+
+        while !players.exit {
+            terminal.draw(|frame| players.draw(frame))?;
+            players.handle_input(events)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> io::Result<()> {
+        if events.poll(Duration::MAX)? {
+            match events.read()? {
+                event::Event::Key(key) => self.key_event(key),
+                event::Event::Mouse(mouse) => self.mouse_event(mouse),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = self.layout(frame.area());
+        frame.render_widget(&*self, frame.area());
+    }
+}
+
+impl Widget for &Players {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        Paragraph::new(Span::from("Players").fg(Color::Red))
+            .centered()
+            .block(Block::bordered().border_set(border::DOUBLE))
+            .render(vert[0], buf);
+
+        let (title, hint) = match self.mode {
+            Mode::Leaderboard(game) => (
+                format!("╡ Leaderboard: {} ╞", registry::GAMES[game].name),
+                " ←/→ game, Esc: back ",
+            ),
+            Mode::Create(_) | Mode::Rename(_) => {
+                (String::from("╡ Players ╞"), " Enter: save, Esc: cancel ")
+            }
+            Mode::Delete => (
+                String::from("╡ Players ╞"),
+                " y: delete, any other key: cancel ",
+            ),
+            _ => (
+                String::from("╡ Players ╞"),
+                " Enter: play as, n: new, r: rename, d: delete, l: leaderboard, Esc: back ",
+            ),
+        };
+        Block::bordered()
+            .border_set(border::DOUBLE)
+            .title(title)
+            .title_bottom(Line::from(hint).set_style(Style::default().fg(Color::DarkGray)))
+            .render(vert[1], buf);
+
+        let [table, line] = Players::body(area);
+        match self.mode {
+            Mode::Leaderboard(_) => self.render_leaderboard(table, buf),
+            _ => self.render_list(table, buf),
+        }
+
+        Paragraph::new(self.prompt()).render(line, buf);
+    }
+}
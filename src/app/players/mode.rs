@@ -0,0 +1,14 @@
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Every player, to pick who plays.
+    #[default]
+    List,
+    /// Typing the name of a new player.
+    Create(String),
+    /// Typing a new name for the selected player.
+    Rename(String),
+    /// Asking before the selected player and all their results are deleted.
+    Delete,
+    /// Players ranked by their average in the game at this registry index.
+    Leaderboard(usize),
+}
@@ -0,0 +1,268 @@
+use std::{fs, path::Path};
+
+use ratatui::{
+    style::Stylize,
+    symbols::Marker,
+    widgets::{Dataset, GraphType},
+};
+
+use super::{Filed, stats::Better};
+
+/// Directory inside the data directory where curves can be overridden.
+const OVERRIDE_DIR: &str = "population";
+/// Number of bins of the player's histogram, unless their scores are whole numbers.
+const BINS: usize = 20;
+
+/// How scores are distributed among people, drawn behind the results chart.
+///
+/// Curves are read from `data/population/<game>.csv`, bundled into the binary, or from
+/// `population/<game>.csv` in the data directory if that file exists. Both use the format
+/// described in `data/population/README.md`.
+#[derive(Debug, Clone)]
+pub struct Population {
+    /// Score and relative frequency, scaled so that the highest frequency is 1.
+    points: Vec<(f64, f64)>,
+    better: Better,
+}
+
+impl Population {
+    /// Loads the curve of `G`, preferring the user's override over the bundled one.
+    pub fn load<G: Filed<'static>>(bundled: &str) -> Self {
+        if let Some(dir) = G::get_dir() {
+            let file = Path::new(&dir)
+                .join(OVERRIDE_DIR)
+                .join(format!("{}.csv", G::NAME));
+            if let Ok(text) = fs::read_to_string(&file) {
+                match Population::parse(&text, G::BETTER) {
+                    Ok(population) => return population,
+                    Err(e) => G::write_log(format!("{}: {e}", file.display())),
+                }
+            }
+        }
+
+        Population::parse(bundled, G::BETTER).expect("bundled population curves are valid")
+    }
+
+    pub fn parse(text: &str, better: Better) -> Result<Self, String> {
+        let mut points: Vec<(f64, f64)> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let point = line
+                .split_once(',')
+                .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+            let Some((x, y)) = point else {
+                return Err(format!("line {}: expected `score,frequency`", i + 1));
+            };
+            if !f64::is_finite(x) || !f64::is_finite(y) || y < 0.0 {
+                return Err(format!(
+                    "line {}: values must be finite, frequencies >= 0",
+                    i + 1
+                ));
+            }
+            if points.last().is_some_and(|&(last, _)| x <= last) {
+                return Err(format!("line {}: scores must be increasing", i + 1));
+            }
+            points.push((x, y));
+        }
+
+        let max = points.iter().map(|&(_, y)| y).fold(0.0, f64::max);
+        if points.len() < 2 || max == 0.0 {
+            return Err(String::from(
+                "need at least two points and a non-zero frequency",
+            ));
+        }
+        for (_, y) in &mut points {
+            *y /= max;
+        }
+
+        Ok(Self { points, better })
+    }
+
+    pub fn better(&self) -> Better {
+        self.better
+    }
+
+    /// Lowest and highest score of the curve.
+    pub fn bounds(&self) -> [f64; 2] {
+        [self.points[0].0, self.points[self.points.len() - 1].0]
+    }
+
+    pub fn dataset(&self) -> Dataset<'_> {
+        Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .cyan()
+            .data(&self.points)
+    }
+
+    /// Outline of a histogram of `scores` over the range of the curve, scaled like the curve so
+    /// that the two shapes can be compared. Scores outside the range count towards the nearest
+    /// bin.
+    pub fn histogram(&self, scores: &[f32]) -> Vec<(f64, f64)> {
+        let [lo, hi] = self.bounds();
+        // Integer scores like levels get one bin per value, centered on it.
+        let (start, width) =
+            if scores.iter().all(|s| s.fract() == 0.0) && hi - lo <= 2.0 * BINS as f64 {
+                (lo - 0.5, 1.0)
+            } else {
+                (lo, (hi - lo) / BINS as f64)
+            };
+        let bins = ((hi - start) / width).ceil().max(1.0) as usize;
+
+        let mut counts = vec![0u32; bins];
+        for &score in scores {
+            let bin = ((score as f64 - start) / width)
+                .floor()
+                .clamp(0.0, (bins - 1) as f64);
+            counts[bin as usize] += 1;
+        }
+        let max = counts.iter().copied().max().unwrap_or_default().max(1) as f64;
+
+        // Only outline the bins from the first to the last one that has scores.
+        let first = counts.iter().position(|&c| c > 0).unwrap_or_default();
+        let last = counts.iter().rposition(|&c| c > 0).unwrap_or_default();
+
+        let mut outline = vec![(start + first as f64 * width, 0.0)];
+        for (i, &count) in counts.iter().enumerate().take(last + 1).skip(first) {
+            let height = count as f64 / max;
+            outline.push((start + i as f64 * width, height));
+            outline.push((start + (i + 1) as f64 * width, height));
+        }
+        outline.push((start + (last + 1) as f64 * width, 0.0));
+        outline
+    }
+
+    /// Percentage of people that `score` beats, taking the area under the curve as the
+    /// population.
+    pub fn percentile(&self, score: f64) -> f64 {
+        let below = self.area_until(score) / self.area_until(f64::INFINITY);
+        let beaten = match self.better {
+            Better::Higher => below,
+            Better::Lower => 1.0 - below,
+        };
+        beaten * 100.0
+    }
+
+    /// Describes [`Population::percentile`] the way a player would say it.
+    pub fn describe(&self, score: f64) -> String {
+        let word = match self.better {
+            Better::Higher => "Better",
+            Better::Lower => "Faster",
+        };
+        format!("{word} than {:.0}% of people", self.percentile(score))
+    }
+
+    /// Area under the curve from its start up to `score`.
+    fn area_until(&self, score: f64) -> f64 {
+        let mut area = 0.0;
+        for pair in self.points.windows(2) {
+            let [(x0, y0), (x1, y1)] = [pair[0], pair[1]];
+            if score >= x1 {
+                area += (y0 + y1) / 2.0 * (x1 - x0);
+            } else {
+                if score > x0 {
+                    let y = y0 + (score - x0) / (x1 - x0) * (y1 - y0);
+                    area += (y0 + y) / 2.0 * (score - x0);
+                }
+                break;
+            }
+        }
+        area
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::reaction_time::ReactionTime;
+
+    const FLAT: &str = "# score, frequency\n0,2\n\n10,2\n";
+
+    #[test]
+    fn parses_comments_blank_lines_and_scales_frequencies() {
+        let population = Population::parse("# x,y\n 1 , 2 \n\n2,4\n3,1\n", Better::Higher).unwrap();
+        assert_eq!(population.points, [(1.0, 0.5), (2.0, 1.0), (3.0, 0.25)]);
+        assert_eq!(population.bounds(), [1.0, 3.0]);
+    }
+
+    #[test]
+    fn rejects_malformed_rows() {
+        let error = |text| Population::parse(text, Better::Higher).unwrap_err();
+        assert!(error("1,1\n2\n").starts_with("line 2:"));
+        assert!(error("1,1\ntwo,1\n").starts_with("line 2:"));
+        assert!(error("1,1\n2,-1\n").starts_with("line 2:"));
+        assert!(error("1,1\n2,inf\n").starts_with("line 2:"));
+        assert!(error("# header\n2,1\n1,1\n").starts_with("line 3:"));
+        assert!(error("1,1\n1,2\n").starts_with("line 2:"));
+        assert!(error("1,1\n").starts_with("need at least two points"));
+        assert!(error("1,0\n2,0\n").starts_with("need at least two points"));
+    }
+
+    #[test]
+    fn bundled_curves_are_valid() {
+        for text in [
+            include_str!("../../data/population/AimTrainer.csv"),
+            include_str!("../../data/population/ChimpTest.csv"),
+            include_str!("../../data/population/NumberMemory.csv"),
+            include_str!("../../data/population/ReactionTime.csv"),
+            include_str!("../../data/population/SequenceMemory.csv"),
+            include_str!("../../data/population/TypingTest.csv"),
+            include_str!("../../data/population/VerbalMemory.csv"),
+            include_str!("../../data/population/VisualMemory.csv"),
+        ] {
+            assert!(Population::parse(text, Better::Higher).is_ok());
+        }
+    }
+
+    #[test]
+    fn overrides_win_unless_they_are_malformed() {
+        let dir = Path::new(&ReactionTime::get_dir().unwrap()).join(OVERRIDE_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join(format!("{}.csv", ReactionTime::NAME));
+        let bundled = "100,1\n500,1\n";
+
+        fs::write(&file, "150,1\n300,3\n450,1\n").unwrap();
+        let population = Population::load::<ReactionTime>(bundled);
+        assert_eq!(population.bounds(), [150.0, 450.0]);
+        assert_eq!(population.better(), Better::Lower);
+
+        fs::write(&file, "150,1\nslow,3\n").unwrap();
+        assert_eq!(
+            Population::load::<ReactionTime>(bundled).bounds(),
+            [100.0, 500.0]
+        );
+    }
+
+    #[test]
+    fn percentile_at_the_ends_and_in_the_middle() {
+        let population = Population::parse(FLAT, Better::Higher).unwrap();
+        assert_eq!(population.percentile(-5.0), 0.0);
+        assert_eq!(population.percentile(0.0), 0.0);
+        assert_eq!(population.percentile(5.0), 50.0);
+        assert_eq!(population.percentile(10.0), 100.0);
+        assert_eq!(population.percentile(15.0), 100.0);
+
+        // Half the area of a symmetric triangle lies left of its peak, an eighth left of the
+        // middle of its rising edge.
+        let triangle = Population::parse("0,0\n10,1\n20,0\n", Better::Higher).unwrap();
+        assert_eq!(triangle.percentile(10.0), 50.0);
+        assert_eq!(triangle.percentile(5.0), 12.5);
+    }
+
+    #[test]
+    fn lower_is_better_flips_the_percentile() {
+        let population = Population::parse(FLAT, Better::Lower).unwrap();
+        assert_eq!(population.percentile(2.0), 80.0);
+        assert_eq!(population.percentile(0.0), 100.0);
+        assert_eq!(population.percentile(10.0), 0.0);
+        assert_eq!(population.describe(2.0), "Faster than 80% of people");
+
+        let higher = Population::parse(FLAT, Better::Higher).unwrap();
+        assert_eq!(higher.describe(2.0), "Better than 20% of people");
+    }
+}
@@ -0,0 +1,287 @@
+use std::{fmt::Write as _, fs, io, time::Duration};
+
+use chrono::Local;
+use ratatui::{
+    Frame, Terminal,
+    backend::Backend,
+    crossterm::event::{self, KeyCode, KeyEvent},
+    layout::{Constraint, Direction, Layout, Margin},
+    style::{Color, Style, Styled, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Bar, BarChart, BarGroup, Block, Paragraph, Widget},
+};
+
+use super::{Game, events::EventSource, players, registry};
+
+/// What a test mainly measures. Each category weighs the same in the composite, however many
+/// tests it has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Speed,
+    Memory,
+    Precision,
+}
+
+impl Category {
+    pub const ALL: [Category; 3] = [Category::Speed, Category::Memory, Category::Precision];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Speed => "Speed",
+            Category::Memory => "Memory",
+            Category::Precision => "Precision",
+        }
+    }
+}
+
+/// Every test's average as a percentile of its population curve, combined by category and into
+/// one composite.
+#[derive(Default)]
+pub struct Profile {
+    exit: bool,
+    /// Percentile of each test's average, in registry order, `None` if it hasn't been played.
+    pub percentiles: Vec<Option<f64>>,
+    /// Outcome of the last export.
+    status: Option<String>,
+}
+
+impl Profile {
+    pub fn load() -> Self {
+        Self {
+            percentiles: registry::GAMES
+                .iter()
+                .map(|entry| {
+                    let savestate = (entry.load)()?;
+                    (savestate.num_entries() > 0)
+                        .then(|| entry.population.percentile(savestate.avg_score() as f64))
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Mean percentile of the played tests of `category`.
+    pub fn category(&self, category: Category) -> Option<f64> {
+        mean(
+            registry::GAMES
+                .iter()
+                .zip(&self.percentiles)
+                .filter(|(entry, _)| entry.category == category)
+                .filter_map(|(_, percentile)| *percentile),
+        )
+    }
+
+    /// Mean of the categories that have been played.
+    pub fn composite(&self) -> Option<f64> {
+        mean(Category::ALL.into_iter().filter_map(|c| self.category(c)))
+    }
+
+    /// The profile as plain text, for the command line and for exporting.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "HumanBenchmark profile, {}\n",
+            Local::now().format("%Y-%m-%d")
+        );
+        for category in Category::ALL {
+            let _ = writeln!(
+                out,
+                "\n{:<20} {:>5}",
+                category.name(),
+                percent(self.category(category))
+            );
+            for (entry, percentile) in registry::GAMES.iter().zip(&self.percentiles) {
+                if entry.category == category {
+                    let _ = writeln!(out, "  {:<18} {:>5}", entry.name, percent(*percentile));
+                }
+            }
+        }
+        let _ = writeln!(
+            out,
+            "\n{:<20} {:>5}",
+            "Composite",
+            percent(self.composite())
+        );
+        out
+    }
+
+    fn export(&mut self) {
+        let Some(file) = get_export_file() else {
+            self.status = Some(String::from("Could not find the data directory"));
+            return;
+        };
+        self.status = Some(match fs::write(&file, self.summary()) {
+            Ok(()) => format!("Saved to {file}"),
+            Err(e) => format!("Could not save to {file}: {e}"),
+        });
+    }
+
+    fn key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('e') => self.export(),
+            _ => (),
+        }
+    }
+
+    fn render_chart(&self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let groups: Vec<(Category, Vec<Bar>)> = Category::ALL
+            .into_iter()
+            .map(|category| {
+                let bars = registry::GAMES
+                    .iter()
+                    .zip(&self.percentiles)
+                    .filter(|(entry, _)| entry.category == category)
+                    .map(|(entry, percentile)| {
+                        // The first word is enough to tell the tests apart.
+                        let label = entry.name.split(' ').next().unwrap_or(entry.name);
+                        let bar = Bar::default()
+                            .label(Line::from(label))
+                            .value(percentile.unwrap_or_default().round() as u64);
+                        // Tests that haven't been played are left as an empty slot.
+                        match percentile {
+                            Some(p) => bar.text_value(format!("{p:.0}%")).style(Color::Cyan),
+                            None => bar,
+                        }
+                    })
+                    .collect();
+                (category, bars)
+            })
+            .collect();
+
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title(" Percentile ");
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // Fit every bar, with a gap between bars and a wider one between categories.
+        let bars = registry::GAMES.len() as u16;
+        let gaps = bars + Category::ALL.len() as u16 * 2;
+        let width = (inner.width.saturating_sub(gaps) / bars).clamp(1, 10);
+
+        let mut chart = BarChart::default()
+            .bar_width(width)
+            .bar_gap(1)
+            .group_gap(3)
+            .max(100);
+        for (category, bars) in &groups {
+            chart = chart.data(
+                BarGroup::default()
+                    .label(Line::from(category.name()).centered())
+                    .bars(bars),
+            );
+        }
+        chart.render(inner, buf);
+    }
+
+    fn render_scores(&self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let mut lines: Vec<Line> = Category::ALL
+            .into_iter()
+            .map(|category| {
+                Line::from(format!(
+                    "{:<10} {:>4}",
+                    category.name(),
+                    percent(self.category(category))
+                ))
+            })
+            .collect();
+        lines.push(Line::default());
+        lines.push(
+            Line::from(format!(
+                "{:<10} {:>4}",
+                "Composite",
+                percent(self.composite())
+            ))
+            .bold(),
+        );
+        if let Some(status) = &self.status {
+            lines.push(Line::default());
+            lines.push(Line::from(status.as_str()).dark_gray().italic());
+        }
+
+        Paragraph::new(lines)
+            .wrap(ratatui::widgets::Wrap { trim: true })
+            .block(Block::bordered().border_set(border::ROUNDED))
+            .render(area, buf);
+    }
+}
+
+fn percent(percentile: Option<f64>) -> String {
+    percentile.map_or_else(|| String::from("-"), |p| format!("{p:.0}%"))
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+fn get_export_file() -> Option<String> {
+    let dir = players::dir(&players::active())?;
+    fs::create_dir_all(&dir).ok()?;
+    Some(dir.join("profile.txt").to_str()?.to_owned())
+}
+
+impl Game for Profile {
+    fn run<B: Backend>(terminal: &mut Terminal<B>, events: &mut dyn EventSource) -> io::Result<()> {
+        let mut profile = Profile::load();
+
+        while !profile.exit {
+            terminal.draw(|frame| profile.draw(frame))?;
+            profile.handle_input(events)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> io::Result<()> {
+        if events.poll(Duration::MAX)?
+            && let event::Event::Key(key) = events.read()?
+        {
+            self.key_event(key);
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        frame.render_widget(&*self, frame.area());
+    }
+}
+
+impl Widget for &Profile {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        Paragraph::new(Span::from("Profile").fg(Color::Red))
+            .centered()
+            .block(Block::bordered().border_set(border::DOUBLE))
+            .render(vert[0], buf);
+
+        Block::bordered()
+            .border_set(border::DOUBLE)
+            .title("╡ Composite ╞")
+            .title_bottom(
+                Line::from(" e: export, Esc: back ")
+                    .set_style(Style::default().fg(Color::DarkGray)),
+            )
+            .render(vert[1], buf);
+
+        let main = vert[1].inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+        let hort = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(24)])
+            .split(main);
+
+        self.render_chart(hort[0], buf);
+        self.render_scores(hort[1], buf);
+    }
+}
@@ -1,50 +1,99 @@
 mod mode;
 
-use super::render_graph;
-use super::{Filed, Game, savestate::SaveState};
+use super::render_results;
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, plausibility::Rules,
+    population::Population, rng::SessionRng, savestate::SaveState, stats::Better,
+};
 use mode::Mode;
 
-use rand::{Rng, rng};
+use rand::Rng;
 use ratatui::style::Stylize;
-use ratatui::symbols::Marker;
 use ratatui::text::Span;
-use ratatui::widgets::{Dataset, GraphType};
+use ratatui::widgets::{Row, Table};
 use std::io;
-use std::time::{Duration, Instant, SystemTime};
+use std::sync::{LazyLock, OnceLock};
+use std::time::{Duration, Instant};
 
 use ratatui::{
-    DefaultTerminal, Frame,
-    crossterm::event::{self, KeyCode, MouseEventKind},
-    layout::{Constraint, Direction, Layout},
+    Frame, Terminal,
+    backend::Backend,
+    crossterm::event::{self, KeyCode, KeyEventKind, MouseEventKind},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style, Styled},
     symbols::border,
     widgets::{Block, Paragraph, Widget},
 };
 
 const FILE_NAME: &str = "ReactionTime";
+const DEFAULT_TRIALS: u32 = 5;
+pub const MAX_TRIALS: u32 = 10;
+const TABLE_WIDTH: u16 = 20;
+
+/// Trials per session given with `play reaction-time --trials`, over the one saved.
+static TRIALS: OnceLock<u32> = OnceLock::new();
+
+pub fn set_trials(trials: u32) {
+    let _ = TRIALS.set(trials.clamp(1, MAX_TRIALS));
+}
+
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<ReactionTime>(include_str!("../../data/population/ReactionTime.csv"))
+});
 
-#[derive(Default)]
 pub struct ReactionTime {
     exit: bool,
-    curr: Option<SystemTime>,
+    curr: Option<Instant>,
     time: f32,
+    trials: u32,
+    session: Vec<f32>,
+    /// Whether a trial of this session was answered by a repeating key.
+    repeated: bool,
     savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
     mode: Mode,
 }
 
+impl Default for ReactionTime {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            curr: None,
+            time: 0.0,
+            trials: DEFAULT_TRIALS,
+            session: Vec::new(),
+            repeated: false,
+            savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng: SessionRng::attempt(FILE_NAME),
+            view: ResultsView::default(),
+            mode: Mode::default(),
+        }
+    }
+}
+
 impl ReactionTime {
-    fn waiting_input(&mut self) -> io::Result<()> {
-        let start = Instant::now();
-        let dur = Duration::from_millis(rng().random_range(3000..6000));
-
-        while start.elapsed() < dur {
-            let remaining = dur.checked_sub(start.elapsed()).unwrap_or(Duration::ZERO);
-            if event::poll(remaining)? {
-                let event = event::read()?;
+    fn waiting_input(&mut self, events: &mut dyn EventSource) -> io::Result<()> {
+        let start = self.clock.now();
+        let dur = Duration::from_millis(self.rng.random_range(3000..6000));
+
+        while self.clock.elapsed(start) < dur {
+            let remaining = dur
+                .checked_sub(self.clock.elapsed(start))
+                .unwrap_or(Duration::ZERO);
+            if events.poll(remaining)? {
+                let event = events.read()?;
                 match event {
                     event::Event::Key(key) => {
                         match key.code {
                             KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+                            // Changing the session before it starts isn't a reaction, it
+                            // only starts the wait over.
+                            KeyCode::Char('+' | '-') if self.session.is_empty() => {
+                                self.change_trials(key.code)
+                            }
                             _ => self.mode = Mode::TooEarly,
                         }
                         return Ok(());
@@ -61,52 +110,131 @@ impl ReactionTime {
         }
 
         self.mode = Mode::Clicking;
-        self.curr = Some(SystemTime::now());
+        self.curr = Some(self.clock.now());
         Ok(())
     }
+
+    fn click(&mut self) {
+        self.time = self.clock.elapsed(self.curr.unwrap()).as_millis() as f32;
+        self.session.push(self.time);
+
+        if (self.session.len() as u32) < self.trials {
+            self.mode = Mode::Trial;
+            return;
+        }
+
+        let total = self.session.iter().sum::<f32>();
+        self.time = total / self.session.len() as f32;
+        let variant = self.variant();
+        let attempt = self.savestate.update(
+            self.time,
+            &variant,
+            Duration::from_millis(total as u64),
+            self.rng.seed(),
+        );
+        attempt.trials = self.session.clone();
+        attempt.flag = Self::RULES.check(&self.session, self.repeated);
+        self.mode = Mode::Results;
+    }
+
+    fn variant(&self) -> String {
+        if self.trials == 1 {
+            String::from("single")
+        } else {
+            format!("session of {}", self.trials)
+        }
+    }
+
+    /// Whether the number of trials can change, which is whenever no session is under way.
+    fn can_change_trials(&self) -> bool {
+        self.mode == Mode::Results || self.session.is_empty()
+    }
+
+    fn change_trials(&mut self, key: KeyCode) {
+        self.trials = match key {
+            KeyCode::Char('+') => (self.trials + 1).min(MAX_TRIALS),
+            _ => self.trials.saturating_sub(1).max(1),
+        };
+    }
+
+    fn next(&mut self) {
+        if self.mode == Mode::Results {
+            self.session.clear();
+            self.repeated = false;
+            self.rng = SessionRng::attempt(FILE_NAME);
+        }
+        self.mode = Mode::Waiting;
+    }
+
+    fn render_trials(&self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let mut rows: Vec<Row> = self
+            .session
+            .iter()
+            .enumerate()
+            .map(|(i, time)| Row::new([format!("#{}", i + 1), format!("{time:.0} ms")]))
+            .collect();
+
+        if self.mode == Mode::Results && self.session.len() > 1 {
+            rows.push(Row::new([String::from("Mean"), format!("{:.1} ms", self.time)]).red());
+        }
+
+        // A finished session stays as long as it was, even once the next one is set longer.
+        let mut block = Block::bordered().border_set(border::ROUNDED);
+        if self.mode == Mode::Results {
+            block = block
+                .title(format!(" {} trials ", self.session.len()))
+                .title_bottom(if self.trials as usize == self.session.len() {
+                    String::from(" +/- trials ")
+                } else {
+                    format!(" next: {}, +/- ", self.trials)
+                });
+        } else {
+            block = block.title(format!(" {}/{} ", self.session.len(), self.trials));
+        }
+
+        Table::new(rows, [Constraint::Length(5), Constraint::Min(0)])
+            .header(Row::new(["Trial", "Time"]).underlined())
+            .block(block)
+            .render(area, buf);
+    }
 }
 
 impl Game for ReactionTime {
-    fn run(terminal: &mut DefaultTerminal) -> io::Result<()> {
-        let mut game = Self::load().unwrap_or_default();
+    fn run<B: Backend>(terminal: &mut Terminal<B>, events: &mut dyn EventSource) -> io::Result<()> {
+        let mut game = Self::load().unwrap_or_else(|| Self::from_savestate(SaveState::default()));
+        game.clock = events.clock();
 
         while !game.exit {
             terminal.draw(|frame| game.draw(frame))?;
-            game.handle_input(terminal)?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
         }
 
         game.save();
         Ok(())
     }
 
-    fn handle_input(&mut self, _: &mut DefaultTerminal) -> io::Result<()> {
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> io::Result<()> {
         match self.mode {
             Mode::Waiting => {
-                self.waiting_input()?;
+                self.waiting_input(events)?;
             }
             Mode::Clicking => {
-                if event::poll(Duration::from_secs(10))? {
-                    let event = event::read()?;
+                if events.poll(Duration::from_secs(10))? {
+                    let event = events.read()?;
                     match event {
                         event::Event::Key(key) => match key.code {
                             KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
                             _ => {
-                                self.savestate.update(
-                                    self.curr.unwrap().elapsed().unwrap().as_millis() as f32,
-                                );
-                                self.time =
-                                    self.curr.unwrap().elapsed().unwrap().as_millis() as f32;
-                                self.mode = Mode::Results;
+                                self.repeated |= key.kind == KeyEventKind::Repeat;
+                                self.click();
                             }
                         },
                         event::Event::Mouse(mouse) => {
                             if let MouseEventKind::Down(_) = mouse.kind {
-                                self.savestate.update(
-                                    self.curr.unwrap().elapsed().unwrap().as_millis() as f32,
-                                );
-                                self.time =
-                                    self.curr.unwrap().elapsed().unwrap().as_millis() as f32;
-                                self.mode = Mode::Results;
+                                self.click();
                             }
                         }
                         _ => (),
@@ -115,18 +243,25 @@ impl Game for ReactionTime {
                     self.mode = Mode::TimeOut;
                 }
             }
-            Mode::Results | Mode::TimeOut | Mode::TooEarly => {
-                if event::poll(Duration::MAX)? {
-                    let event = event::read()?;
+            Mode::Results | Mode::Trial | Mode::TimeOut | Mode::TooEarly => {
+                if events.poll(Duration::MAX)? {
+                    let event = events.read()?;
                     match event {
                         event::Event::Key(key) => match key.code {
                             KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
-                            KeyCode::Enter | KeyCode::Char('r') => self.mode = Mode::Waiting,
+                            KeyCode::Enter | KeyCode::Char('r') => self.next(),
+                            KeyCode::Char('+' | '-') if self.can_change_trials() => {
+                                self.change_trials(key.code)
+                            }
+                            KeyCode::Tab if self.mode == Mode::Results => self.view.tab.toggle(),
+                            KeyCode::Char('h') if self.mode == Mode::Results => {
+                                self.view.toggle_histogram()
+                            }
                             _ => (),
                         },
                         event::Event::Mouse(mouse) => {
                             if let MouseEventKind::Down(_) = mouse.kind {
-                                self.mode = Mode::Waiting;
+                                self.next();
                             }
                         }
                         _ => (),
@@ -137,21 +272,36 @@ impl Game for ReactionTime {
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        frame.render_widget(&*self, frame.area());
     }
 }
 
 impl Filed<'_> for ReactionTime {
     const NAME: &'static str = FILE_NAME;
+    const BETTER: Better = Better::Lower;
+    const RULES: Rules = Rules {
+        floor: Some(100.0),
+        min_spread: Some(0.02),
+    };
     type SaveState = SaveState;
 
     fn get_savestate(&self) -> Self::SaveState {
-        self.savestate
+        SaveState {
+            session_trials: Some(self.trials),
+            ..self.savestate.clone()
+        }
     }
 
     fn from_savestate(savestate: Self::SaveState) -> Self {
+        let trials = TRIALS
+            .get()
+            .copied()
+            .or(savestate.session_trials)
+            .unwrap_or(DEFAULT_TRIALS)
+            .clamp(1, MAX_TRIALS);
         Self {
+            trials,
             savestate,
             ..Default::default()
         }
@@ -194,6 +344,11 @@ impl Widget for &ReactionTime {
 
         let block = Block::bordered().border_set(border::DOUBLE);
 
+        let side = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(TABLE_WIDTH)])
+            .split(main)[1];
+
         match self.mode {
             Mode::Waiting => {
                 block.title("╡ Game ╞").render(vert[1], buf);
@@ -205,7 +360,12 @@ impl Widget for &ReactionTime {
                     .set_style(Color::Black)
                     .render(center[1], buf);
 
-                Paragraph::new("Esc/'q' to quit")
+                let hint = if self.session.is_empty() {
+                    format!("+/- trials ({}), Esc/'q' to quit", self.trials)
+                } else {
+                    String::from("Esc/'q' to quit")
+                };
+                Paragraph::new(hint)
                     .centered()
                     .set_style(Color::Black)
                     .render(center[4], buf);
@@ -222,6 +382,27 @@ impl Widget for &ReactionTime {
                 Paragraph::new("'r' to restart and Esc/'q' to quit")
                     .centered()
                     .render(center[4], buf);
+
+                if !self.session.is_empty() {
+                    Paragraph::new("That one doesn't count, the session goes on")
+                        .centered()
+                        .render(center[2], buf);
+                    self.render_trials(side, buf);
+                }
+            }
+            Mode::Trial => {
+                block
+                    .title(format!("╡ Trial {}/{} ╞", self.session.len(), self.trials))
+                    .render(vert[1], buf);
+                Paragraph::new(format!("{:.0} ms", self.time))
+                    .centered()
+                    .render(center[1], buf);
+
+                Paragraph::new("Click/Enter for the next trial and Esc/'q' to quit")
+                    .centered()
+                    .render(center[4], buf);
+
+                self.render_trials(side, buf);
             }
             Mode::Clicking => {
                 block.title("╡ Clicking ╞").render(vert[1], buf);
@@ -247,48 +428,83 @@ impl Widget for &ReactionTime {
                 Paragraph::new("'r' to restart and Esc/'q' to quit")
                     .centered()
                     .render(center[4], buf);
+
+                if !self.session.is_empty() {
+                    self.render_trials(side, buf);
+                }
             }
             Mode::Results => {
                 block.title("╡ Results ╞").render(vert[1], buf);
 
-                let dataset = Dataset::default()
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .cyan()
-                    .data(&[
-                        (0.0, (0.0 / 270.0)),
-                        (25.0, (0.0 / 270.0)),
-                        (50.0, (0.0 / 270.0)),
-                        (75.0, (0.0 / 270.0)),
-                        (100.0, (0.0 / 270.0)),
-                        (115.0, (5.0 / 270.0)),
-                        (125.0, (14.0 / 270.0)),
-                        (150.0, (78.0 / 270.0)),
-                        (175.0, (205.0 / 270.0)),
-                        (200.0, (250.0 / 270.0)),
-                        (225.0, (230.0 / 270.0)),
-                        (250.0, (160.0 / 270.0)),
-                        (275.0, (90.0 / 270.0)),
-                        (300.0, (50.0 / 270.0)),
-                        (325.0, (30.0 / 270.0)),
-                        (350.0, (17.0 / 270.0)),
-                        (375.0, (10.0 / 270.0)),
-                        (400.0, (8.0 / 270.0)),
-                        (425.0, (6.0 / 270.0)),
-                        (450.0, (5.0 / 270.0)),
-                        (475.0, (3.0 / 270.0)),
-                        (500.0, (3.0 / 270.0)),
-                    ]);
-
-                render_graph(
-                    self.savestate.avg_score as f64,
+                let hort = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Length(TABLE_WIDTH), Constraint::Min(0)])
+                    .split(main);
+
+                self.render_trials(hort[0], buf);
+
+                render_results(
+                    self.view,
                     self.time as f64,
-                    dataset,
-                    [0.0, 500.0],
-                    main,
+                    &self.savestate,
+                    &POPULATION,
+                    hort[1],
                     buf,
                 );
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::events::ScriptedEvents;
+
+    #[test]
+    fn session_is_timed_on_the_clock() {
+        let events = ScriptedEvents::default().with_clock(Clock::manual());
+        let mut game = ReactionTime {
+            trials: 3,
+            clock: events.clock(),
+            ..Default::default()
+        };
+
+        for reaction in [250, 300, 350] {
+            // Nothing happens until the screen turns green, after however long it takes.
+            game.handle_input(&mut events.clone()).unwrap();
+            assert_eq!(game.mode, Mode::Clicking);
+
+            let mut events = events
+                .clone()
+                .wait(Duration::from_millis(reaction))
+                .key(KeyCode::Char(' '));
+            game.handle_input(&mut events).unwrap();
+            if game.mode == Mode::Trial {
+                game.handle_input(&mut events.key(KeyCode::Enter)).unwrap();
+            }
+        }
+
+        assert_eq!(game.mode, Mode::Results);
+        let attempt = game.savestate.attempts.last().unwrap();
+        assert_eq!(attempt.trials, [250.0, 300.0, 350.0]);
+        assert_eq!(attempt.score, 300.0);
+        assert_eq!(attempt.duration_ms, 900);
+        assert_eq!(attempt.variant, "session of 3");
+        assert_eq!(attempt.flag, None);
+    }
+
+    #[test]
+    fn waiting_too_long_times_out() {
+        let events = ScriptedEvents::default().with_clock(Clock::manual());
+        let mut game = ReactionTime {
+            clock: events.clock(),
+            ..Default::default()
+        };
+
+        game.handle_input(&mut events.clone()).unwrap();
+        game.handle_input(&mut events.clone()).unwrap();
+        assert_eq!(game.mode, Mode::TimeOut);
+        assert!(game.session.is_empty());
+    }
+}
@@ -1,9 +1,10 @@
-#[derive(Default)]
+#[derive(Default, Debug, PartialEq, Eq)]
 pub enum Mode {
     #[default]
     Waiting,
     TooEarly,
     Clicking,
+    Trial,
     TimeOut,
     Results,
 }
@@ -0,0 +1,168 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{self, KeyCode},
+    layout::{Constraint, Direction, Layout, Margin},
+    style::{Color, Style, Styled, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, Wrap},
+};
+
+use super::{events::EventSource, registry::Entry, savestate::SaveState};
+
+/// Asks what to do about a save file that can't be read before its game runs. The game would
+/// never save over the file, so the choice is between restoring its newest good backup, moving
+/// it out of the way to start over, and leaving it alone.
+pub struct Recovery<'a> {
+    entry: &'a Entry,
+    file: String,
+    error: String,
+    /// Newest backup that can be read, what it holds and when it was written.
+    backup: Option<(String, SaveState, Option<DateTime<Local>>)>,
+    /// Whether the file was restored or backed up; `None` while the player hasn't decided.
+    outcome: Option<bool>,
+    /// Why the last backup or restore failed.
+    status: Option<String>,
+}
+
+impl<'a> Recovery<'a> {
+    /// Returns whether the game can run, which is once the file has been restored or backed up.
+    pub fn run(
+        entry: &'a Entry,
+        error: String,
+        terminal: &mut DefaultTerminal,
+        events: &mut dyn EventSource,
+    ) -> io::Result<bool> {
+        let Some(file) = (entry.save_file)() else {
+            return Ok(false);
+        };
+        let backup = (entry.backup)().map(|(path, savestate)| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, savestate, modified.map(DateTime::from))
+        });
+        let mut recovery = Recovery {
+            entry,
+            file,
+            error,
+            backup,
+            outcome: None,
+            status: None,
+        };
+
+        loop {
+            terminal.draw(|frame| frame.render_widget(&recovery, frame.area()))?;
+            if events.poll(Duration::MAX)?
+                && let event::Event::Key(key) = events.read()?
+            {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') => recovery.outcome = Some(false),
+                    KeyCode::Char('b') => match back_up(&recovery.file) {
+                        Ok(_) => recovery.outcome = Some(true),
+                        Err(e) => recovery.status = Some(format!("Could not back it up: {e}")),
+                    },
+                    KeyCode::Char('r') if recovery.backup.is_some() => match recovery.restore() {
+                        Ok(()) => recovery.outcome = Some(true),
+                        Err(e) => recovery.status = Some(format!("Could not restore it: {e}")),
+                    },
+                    _ => (),
+                }
+            }
+            if let Some(outcome) = recovery.outcome {
+                return Ok(outcome);
+            }
+        }
+    }
+}
+
+impl Recovery<'_> {
+    /// Puts the backup in place of the save file, which is kept next to it.
+    fn restore(&self) -> io::Result<()> {
+        let Some((backup, ..)) = &self.backup else {
+            return Ok(());
+        };
+        back_up(&self.file)?;
+        fs::copy(backup, &self.file)?;
+        Ok(())
+    }
+}
+
+/// Moves a save file out of the way, next to where it was, and returns where it went.
+pub fn back_up(file: &str) -> io::Result<String> {
+    let path = Path::new(file);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("save");
+    let backup = path.with_file_name(format!(
+        "{stem}.unreadable-{}.json",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::rename(path, &backup)?;
+    Ok(backup.to_string_lossy().into_owned())
+}
+
+impl Widget for &Recovery<'_> {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        Paragraph::new(Span::from(self.entry.name).fg(Color::Red))
+            .centered()
+            .block(Block::bordered().border_set(border::DOUBLE))
+            .render(vert[0], buf);
+
+        let hint = if self.backup.is_some() {
+            " r: restore the backup, b: back up and start over, Esc: leave it "
+        } else {
+            " b: back up and start over, Esc: leave it "
+        };
+        Block::bordered()
+            .border_set(border::DOUBLE)
+            .title("╡ Save file ╞")
+            .title_bottom(Line::from(hint).set_style(Style::default().fg(Color::DarkGray)))
+            .render(vert[1], buf);
+
+        let main = vert[1].inner(Margin {
+            horizontal: 2,
+            vertical: 2,
+        });
+        let mut lines = vec![
+            Line::from(format!("{} can't be read:", self.file)),
+            Line::from(self.error.as_str()).red(),
+            Line::default(),
+            Line::from("It hasn't been touched, and results won't be saved over it."),
+        ];
+        match &self.backup {
+            Some((path, savestate, modified)) => {
+                let when = modified
+                    .map(|m| format!(" from {}", m.format("%Y-%m-%d %H:%M")))
+                    .unwrap_or_default();
+                lines.extend([
+                    Line::from(format!(
+                        "The newest backup that can be read is {path}{when}, with {} results.",
+                        savestate.attempts.len() as u32 + savestate.legacy_entries
+                    )),
+                    Line::from(
+                        "Restore it (the damaged file is kept), start over, or leave it to fix by hand.",
+                    ),
+                ]);
+            }
+            None => lines.push(Line::from(
+                "Back it up to start a new history, or leave it to fix it by hand.",
+            )),
+        }
+        if let Some(status) = &self.status {
+            lines.push(Line::default());
+            lines.push(Line::from(status.as_str()).italic());
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .centered()
+            .render(main, buf);
+    }
+}
@@ -0,0 +1,50 @@
+use ratatui::layout::{Position, Rect};
+
+/// Clickable parts of a screen, named by `K`, as they were laid out for the last frame.
+///
+/// Screens fill this in `Game::draw` and read it back both when rendering and when a click comes
+/// in, so that what gets clicked is always what was drawn.
+#[derive(Debug, Clone)]
+pub struct Regions<K> {
+    regions: Vec<(K, Rect)>,
+}
+
+impl<K> Default for Regions<K> {
+    fn default() -> Self {
+        Self {
+            regions: Vec::new(),
+        }
+    }
+}
+
+impl<K> FromIterator<(K, Rect)> for Regions<K> {
+    fn from_iter<I: IntoIterator<Item = (K, Rect)>>(iter: I) -> Self {
+        Self {
+            regions: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<K: Copy + PartialEq> Regions<K> {
+    pub fn push(&mut self, key: K, rect: Rect) {
+        self.regions.push((key, rect));
+    }
+
+    pub fn get(&self, key: K) -> Option<Rect> {
+        self.iter().find(|(k, _)| *k == key).map(|(_, rect)| rect)
+    }
+
+    /// Region under the given cell. Regions pushed later are on top.
+    pub fn at(&self, column: u16, row: u16) -> Option<K> {
+        let position = Position::new(column, row);
+        self.regions
+            .iter()
+            .rev()
+            .find(|(_, rect)| rect.contains(position))
+            .map(|(key, _)| *key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (K, Rect)> + '_ {
+        self.regions.iter().copied()
+    }
+}
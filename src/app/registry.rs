@@ -0,0 +1,165 @@
+use std::{io, sync::LazyLock};
+
+use ratatui::DefaultTerminal;
+
+use super::{
+    Filed, Game,
+    aim_trainer::{self, AimTrainer},
+    chimp_test::{self, ChimpTest},
+    events::EventSource,
+    number_memory::{self, NumberMemory},
+    plausibility::Rules,
+    population::Population,
+    profile::Category,
+    reaction_time::{self, ReactionTime},
+    savestate::SaveState,
+    sequence_memory::{self, SequenceMemory},
+    stats::Better,
+    typing_test::{self, TypingTest},
+    verbal_memory::{self, VerbalMemory},
+    visual_memory::{self, VisualMemory},
+};
+
+/// Everything the menu and the command line need to know about a test.
+pub struct Entry {
+    pub name: &'static str,
+    /// Name used on the command line.
+    pub id: &'static str,
+    pub description: &'static str,
+    pub better: Better,
+    /// What a plausible attempt looks like, also for attempts that come from elsewhere.
+    pub rules: Rules,
+    /// What a score is counted in.
+    pub unit: &'static str,
+    pub run: fn(&mut DefaultTerminal, &mut dyn EventSource) -> io::Result<()>,
+    pub load: fn() -> Option<SaveState>,
+    /// Like `load`, but tells a missing save file apart from one that can't be read.
+    pub read: fn() -> Result<Option<SaveState>, String>,
+    /// Newest backup of the save file that can be read, and where it is.
+    pub backup: fn() -> Option<(String, SaveState)>,
+    /// Like `read`, for any player.
+    pub read_player: fn(&str) -> Result<Option<SaveState>, String>,
+    /// Replaces the save state, unless the save file can't be read.
+    pub write: fn(&SaveState) -> Result<(), String>,
+    pub save_file: fn() -> Option<String>,
+    pub population: &'static LazyLock<Population>,
+    /// Part of the composite profile this test counts towards.
+    pub category: Category,
+}
+
+/// Every test, in menu order.
+pub const GAMES: [Entry; 8] = [
+    entry::<ReactionTime>(
+        "Reaction Time",
+        "reaction-time",
+        "Click as soon as the screen turns green",
+        "ms",
+        &reaction_time::POPULATION,
+        Category::Speed,
+    ),
+    entry::<SequenceMemory>(
+        "Sequence Memory",
+        "sequence-memory",
+        "Repeat an ever growing sequence of squares",
+        "level",
+        &sequence_memory::POPULATION,
+        Category::Memory,
+    ),
+    entry::<AimTrainer>(
+        "Aim Trainer",
+        "aim-trainer",
+        "Hit 30 targets as quickly as you can",
+        "ms",
+        &aim_trainer::POPULATION,
+        Category::Precision,
+    ),
+    entry::<NumberMemory>(
+        "Number Memory",
+        "number-memory",
+        "Remember the longest number you can",
+        "digits",
+        &number_memory::POPULATION,
+        Category::Memory,
+    ),
+    entry::<VerbalMemory>(
+        "Verbal Memory",
+        "verbal-memory",
+        "Keep as many words in short term memory as possible",
+        "words",
+        &verbal_memory::POPULATION,
+        Category::Memory,
+    ),
+    entry::<ChimpTest>(
+        "Chimp Test",
+        "chimp-test",
+        "Are you smarter than a chimpanzee?",
+        "numbers",
+        &chimp_test::POPULATION,
+        Category::Memory,
+    ),
+    entry::<VisualMemory>(
+        "Visual Memory",
+        "visual-memory",
+        "Remember an increasingly large board of squares",
+        "level",
+        &visual_memory::POPULATION,
+        Category::Memory,
+    ),
+    entry::<TypingTest>(
+        "Typing",
+        "typing",
+        "How many words per minute can you type?",
+        "wpm",
+        &typing_test::POPULATION,
+        Category::Speed,
+    ),
+];
+
+/// Entry of the test `G`, with everything but what the test can't tell about itself taken
+/// from its implementation.
+const fn entry<G: Game + Filed<'static, SaveState = SaveState>>(
+    name: &'static str,
+    id: &'static str,
+    description: &'static str,
+    unit: &'static str,
+    population: &'static LazyLock<Population>,
+    category: Category,
+) -> Entry {
+    Entry {
+        name,
+        id,
+        description,
+        better: G::BETTER,
+        rules: G::RULES,
+        unit,
+        run: G::run,
+        load: G::load_savestate,
+        read: G::read_savestate,
+        backup: G::read_backup,
+        read_player: G::read_player_savestate,
+        write: G::write_savestate,
+        save_file: G::get_save_file,
+        population,
+        category,
+    }
+}
+
+/// Looks a test up by its id or display name, ignoring case and punctuation.
+pub fn find(name: &str) -> Option<&'static Entry> {
+    position(name).map(|game| &GAMES[game])
+}
+
+/// Like [`find`], but gives the index of the test in [`GAMES`].
+pub fn position(name: &str) -> Option<usize> {
+    let normalize = |s: &str| -> String {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let name = normalize(name);
+
+    GAMES
+        .iter()
+        .position(|entry| normalize(entry.id) == name || normalize(entry.name) == name)
+}
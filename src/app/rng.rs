@@ -0,0 +1,97 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+static SEED: OnceLock<u64> = OnceLock::new();
+
+/// Seed of this session. It's random unless it was set with `--seed` before any game started.
+pub fn seed() -> u64 {
+    *SEED.get_or_init(rand::random)
+}
+
+pub fn set_seed(seed: u64) {
+    let _ = SEED.set(seed);
+}
+
+/// Attempts started so far this session, by game.
+static ATTEMPTS: Mutex<BTreeMap<&str, u64>> = Mutex::new(BTreeMap::new());
+
+/// Random number generator of one attempt. Its seed comes from the session seed, the game and
+/// how many attempts of that game were started before, so the same session seed produces the
+/// same targets, numbers and texts attempt by attempt, and the seed saved with an attempt is
+/// the one it was played with.
+///
+/// The generator is ChaCha8 rather than `StdRng`, whose algorithm `rand` is free to change, so
+/// that a seed keeps producing the same attempt across versions.
+#[derive(Debug, Clone)]
+pub struct SessionRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl SessionRng {
+    /// Generator of the next attempt of `game`.
+    pub fn attempt(game: &'static str) -> Self {
+        let index = {
+            let mut attempts = ATTEMPTS.lock().unwrap_or_else(PoisonError::into_inner);
+            let count = attempts.entry(game).or_default();
+            *count += 1;
+            *count - 1
+        };
+        let seed = attempt_seed(seed(), game, index);
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+fn attempt_seed(session: u64, game: &str, index: u64) -> u64 {
+    // FNV-1a and ChaCha8, since neither the standard hasher nor `StdRng` is guaranteed to
+    // stay the same between Rust and `rand` versions.
+    let game = game.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let base = ChaCha8Rng::seed_from_u64(session ^ game).next_u64();
+    ChaCha8Rng::seed_from_u64(base.wrapping_add(index)).next_u64()
+}
+
+impl RngCore for SessionRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.rng.fill_bytes(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_attempt_has_a_seed_of_its_own() {
+        assert_eq!(attempt_seed(7, "Game", 0), attempt_seed(7, "Game", 0));
+        assert_ne!(attempt_seed(7, "Game", 0), attempt_seed(7, "Game", 1));
+        assert_ne!(attempt_seed(7, "Game", 0), attempt_seed(7, "Other", 0));
+        assert_ne!(attempt_seed(7, "Game", 0), attempt_seed(8, "Game", 0));
+
+        let (first, second) = (SessionRng::attempt("Test"), SessionRng::attempt("Test"));
+        assert_ne!(first.seed(), second.seed());
+        let mut replayed = ChaCha8Rng::seed_from_u64(second.seed());
+        assert_eq!(second.clone().next_u64(), replayed.next_u64());
+    }
+}
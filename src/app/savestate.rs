@@ -1,15 +1,97 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize)]
+use super::{
+    plausibility::Flag,
+    stats::{Better, Stats},
+};
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct SaveState {
-    pub avg_score: f32,
-    pub num_entries: u32,
+    /// Mean of the results recorded before individual attempts were kept.
+    pub legacy_avg: f32,
+    pub legacy_entries: u32,
+    #[serde(default)]
+    pub attempts: Vec<Attempt>,
+    /// Trials per session the player chose last, for games that score a session of trials.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_trials: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Attempt {
+    pub score: f32,
+    pub timestamp: DateTime<Local>,
+    #[serde(default)]
+    pub variant: String,
+    #[serde(default)]
+    pub duration_ms: u64,
+    /// Seed the attempt was played with, which reproduces its targets, numbers or text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Individual trial results for games that score the mean of a session.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trials: Vec<f32>,
+    /// Set when the attempt broke a plausibility rule, which keeps it out of averages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<Flag>,
+}
+
+impl Attempt {
+    /// Whether the attempt counts towards averages and statistics.
+    pub fn counts(&self) -> bool {
+        self.flag.is_none()
+    }
 }
 
 impl SaveState {
-    pub fn update(&mut self, score: f32) {
-        self.avg_score =
-            (self.avg_score * self.num_entries as f32 + score) / (self.num_entries + 1) as f32;
-        self.num_entries += 1;
+    pub fn update(
+        &mut self,
+        score: f32,
+        variant: &str,
+        duration: Duration,
+        seed: u64,
+    ) -> &mut Attempt {
+        self.attempts.push(Attempt {
+            score,
+            timestamp: Local::now(),
+            variant: variant.to_owned(),
+            duration_ms: duration.as_millis() as u64,
+            seed: Some(seed),
+            trials: Vec::new(),
+            flag: None,
+        });
+        self.attempts.last_mut().unwrap()
+    }
+
+    /// Number of results that count, including the ones only known through the legacy
+    /// aggregate.
+    pub fn num_entries(&self) -> u32 {
+        self.legacy_entries + self.counted().count() as u32
+    }
+
+    /// Attempts that count, in the order they were played.
+    pub fn counted(&self) -> impl Iterator<Item = &Attempt> {
+        self.attempts.iter().filter(|a| a.counts())
+    }
+
+    /// Statistics over the recorded attempts that count. Results only known through the legacy
+    /// aggregate have no individual scores, so they're left out.
+    pub fn stats(&self, better: Better) -> Option<Stats> {
+        let scores: Vec<f32> = self.counted().map(|a| a.score).collect();
+        Stats::new(&scores, better)
+    }
+
+    pub fn avg_score(&self) -> f32 {
+        let entries = self.num_entries();
+        if entries == 0 {
+            return 0.0;
+        }
+
+        let sum = self.legacy_avg * self.legacy_entries as f32
+            + self.counted().map(|a| a.score).sum::<f32>();
+        sum / entries as f32
     }
 }
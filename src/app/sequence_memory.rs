@@ -1,27 +1,37 @@
 mod mode;
 
-use super::{Filed, Game, render_graph, savestate::SaveState};
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, population::Population,
+    regions::Regions, render_results, rng::SessionRng, savestate::SaveState,
+};
 use mode::Mode;
 
-use rand::{Rng, rng};
+use rand::Rng;
 use std::{
     io,
+    sync::LazyLock,
     time::{Duration, Instant},
 };
 
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame, Terminal,
+    backend::Backend,
     crossterm::event::{self, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style, Styled, Stylize},
-    symbols::{Marker, border},
+    symbols::border,
     text::Span,
-    widgets::{Block, Dataset, GraphType, Paragraph, Widget},
+    widgets::{Block, Paragraph, Widget},
 };
 
 const FILE_NAME: &str = "SequenceMemory";
+const VARIANT: &str = "standard";
 const FADE_OUT: u64 = 500;
 
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<SequenceMemory>(include_str!("../../data/population/SequenceMemory.csv"))
+});
+
 pub struct SequenceMemory {
     exit: bool,
 
@@ -29,75 +39,90 @@ pub struct SequenceMemory {
     scramble: Vec<u8>,
     mode: Mode,
     clicked: Option<(u8, Instant)>,
+    start: Option<Instant>,
     savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
+    regions: Regions<u8>,
 }
 
 impl Default for SequenceMemory {
     fn default() -> Self {
+        let mut rng = SessionRng::attempt(FILE_NAME);
         Self {
             exit: false,
             curr: vec![],
-            scramble: vec![rng().random_range(0..9)],
+            scramble: vec![rng.random_range(0..9)],
             mode: Mode::Waiting,
             clicked: None,
+            start: None,
             savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng,
+            view: ResultsView::default(),
+            regions: Regions::default(),
         }
     }
 }
 
 impl SequenceMemory {
-    fn mouse_input(&mut self, e: MouseEvent, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        if let MouseEventKind::Down(_) = e.kind {
-            let mouse_rect = Rect::new(e.column, e.row, 1, 1);
-            let area = terminal.get_frame().area();
-
-            let vert = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3), // Title
-                    Constraint::Min(0),    // Body
-                ])
-                .split(area);
-
-            let main = vert[1].inner(ratatui::layout::Margin {
+    fn mouse_input(&mut self, e: MouseEvent) {
+        if let MouseEventKind::Down(_) = e.kind
+            && let Some(index) = self.regions.at(e.column, e.row)
+        {
+            self.clicked = Some((index, self.clock.now()));
+            self.curr.push(index);
+            if self.check_validity() {
+                self.mode = Mode::Pause(self.clock.now());
+                let old = self.scramble.last().unwrap();
+                let rng = &mut self.rng;
+                let mut new = rng.random_range(0..9);
+                while new == *old {
+                    new = rng.random_range(0..9);
+                }
+                self.scramble.push(new);
+                self.curr.clear();
+            }
+        }
+    }
+
+    /// Lays out the nine squares, numbered row by row.
+    fn layout(&self, area: Rect) -> Regions<u8> {
+        match self.mode {
+            Mode::Watching(_) | Mode::Clicking | Mode::Pause(_) => (),
+            Mode::Waiting | Mode::Results => return Regions::default(),
+        }
+
+        let main = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Title
+                Constraint::Min(0),    // Body
+            ])
+            .split(area)[1]
+            .inner(ratatui::layout::Margin {
                 horizontal: 1,
                 vertical: 1,
             });
 
-            let rows = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Min(0),    // ---
-                    Constraint::Length(4), // Title
-                    Constraint::Length(4), // Title
-                    Constraint::Length(4), // Title
-                    Constraint::Min(0),    // ---
-                ])
-                .split(main);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),    // ---
+                Constraint::Length(1), // Score
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Min(0), // ---
+            ])
+            .split(main);
 
-            let lenth = rows[1].height * 2;
+        let lenth = rows[2].height * 2;
 
-            let rects = [
-                Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(lenth),
-                        Constraint::Length(lenth),
-                        Constraint::Length(lenth),
-                        Constraint::Min(0),
-                    ])
-                    .split(rows[1]),
-                Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(lenth),
-                        Constraint::Length(lenth),
-                        Constraint::Length(lenth),
-                        Constraint::Min(0),
-                    ])
-                    .split(rows[2]),
+        rows[2..5]
+            .iter()
+            .flat_map(|row| {
                 Layout::default()
                     .direction(Direction::Horizontal)
                     .constraints([
@@ -107,40 +132,26 @@ impl SequenceMemory {
                         Constraint::Length(lenth),
                         Constraint::Min(0),
                     ])
-                    .split(rows[3]),
-            ];
-
-            let mut index = 0;
-            'outer: for y in rects {
-                for el in &y[1..4] {
-                    if mouse_rect.intersects(*el) {
-                        self.clicked = Some((index, Instant::now()));
-                        self.curr.push(index);
-                        if self.check_validity() {
-                            self.mode = Mode::Pause(Instant::now());
-                            let old = self.scramble.last().unwrap();
-                            let mut rng = rng();
-                            let mut new = rng.random_range(0..9);
-                            while new == *old {
-                                new = rng.random_range(0..9);
-                            }
-                            self.scramble.push(new);
-                            self.curr.clear();
-                        }
-                        break 'outer;
-                    }
-                    index += 1;
-                }
-            }
-        }
-        Ok(())
+                    .split(*row)[1..4]
+                    .to_vec()
+            })
+            .zip(0..)
+            .map(|(rect, index)| (index, rect))
+            .collect()
     }
 
     fn check_validity(&mut self) -> bool {
         for x in 0..self.curr.len() {
             if self.curr[x] != self.scramble[x] {
                 self.mode = Mode::Results;
-                self.savestate.update(self.get_score() as f32);
+                self.savestate.update(
+                    self.get_score() as f32,
+                    VARIANT,
+                    self.start
+                        .map(|s| self.clock.elapsed(s))
+                        .unwrap_or_default(),
+                    self.rng.seed(),
+                );
                 self.curr.clear();
                 return false;
             }
@@ -149,48 +160,58 @@ impl SequenceMemory {
         self.curr.len() == self.scramble.len()
     }
 
+    fn play(&mut self) {
+        self.mode = Mode::Watching(0);
+        self.start = Some(self.clock.now());
+    }
+
     fn get_score(&self) -> u32 {
         self.scramble.len().saturating_sub(1) as u32
     }
 
     fn reset(&mut self) {
-        let st = self.savestate;
+        let st = std::mem::take(&mut self.savestate);
         *self = Self {
             savestate: st,
-            scramble: vec![rng().random_range(0..9)],
+            clock: self.clock.clone(),
+            view: self.view,
             ..Default::default()
         };
     }
 }
 
 impl Game for SequenceMemory {
-    fn run(terminal: &mut DefaultTerminal) -> io::Result<()> {
+    fn run<B: Backend>(terminal: &mut Terminal<B>, events: &mut dyn EventSource) -> io::Result<()> {
         let mut game = Self::load().unwrap_or_default();
+        game.clock = events.clock();
 
         while !game.exit {
             terminal.draw(|frame| game.draw(frame))?;
-            game.handle_input(terminal)?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
         }
 
         game.save();
         Ok(())
     }
 
-    fn handle_input(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> io::Result<()> {
         match self.mode {
             Mode::Waiting => {
-                if event::poll(Duration::MAX)? {
-                    let event = event::read()?;
+                if events.poll(Duration::MAX)? {
+                    let event = events.read()?;
 
                     match event {
                         event::Event::Key(key) => match key.code {
-                            KeyCode::Enter | KeyCode::Char(' ') => self.mode = Mode::Watching(0),
+                            KeyCode::Enter | KeyCode::Char(' ') => self.play(),
                             KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
                             _ => (),
                         },
                         event::Event::Mouse(mouse) => {
                             if let MouseEventKind::Down(_) = mouse.kind {
-                                self.mode = Mode::Watching(0);
+                                self.play();
                             }
                         }
                         _ => (),
@@ -200,9 +221,9 @@ impl Game for SequenceMemory {
             Mode::Watching(step) => {
                 let mut dur = Duration::from_millis(FADE_OUT + 100);
                 while dur.as_millis() != 0 {
-                    let then = Instant::now();
-                    if event::poll(dur)? {
-                        let event = event::read()?;
+                    let then = self.clock.now();
+                    if events.poll(dur)? {
+                        let event = events.read()?;
                         if let event::Event::Key(key) = event {
                             match key.code {
                                 KeyCode::Esc | KeyCode::Char('q') => {
@@ -217,7 +238,7 @@ impl Game for SequenceMemory {
                             }
                         }
                     }
-                    dur = dur.saturating_sub(then.elapsed());
+                    dur = dur.saturating_sub(self.clock.elapsed(then));
                 }
 
                 self.mode = if step == self.scramble.len().saturating_sub(1) as u32 {
@@ -227,9 +248,16 @@ impl Game for SequenceMemory {
                 };
             }
             Mode::Pause(instant) => {
-                let dur = Duration::from_millis(FADE_OUT * 2).saturating_sub(instant.elapsed());
-                if event::poll(dur / 2)? {
-                    match event::read()? {
+                // Wakes up once the clicked square has faded out and again when the pause is over.
+                let fade = Duration::from_millis(FADE_OUT);
+                let elapsed = self.clock.elapsed(instant);
+                let dur = if elapsed < fade {
+                    fade - elapsed
+                } else {
+                    (fade * 2).saturating_sub(elapsed)
+                };
+                if events.poll(dur)? {
+                    match events.read()? {
                         event::Event::Key(KeyEvent {
                             code: KeyCode::Esc, ..
                         })
@@ -242,24 +270,22 @@ impl Game for SequenceMemory {
                         _ => (),
                     }
                 }
-                if dur.is_zero() {
+                if self.mode == Mode::Pause(instant) && self.clock.elapsed(instant) >= fade * 2 {
                     self.mode = Mode::Watching(0);
                 }
             }
             Mode::Clicking => {
                 let dur = if let Some(val) = self.clicked {
                     Duration::from_millis(FADE_OUT)
-                        .checked_sub(val.1.elapsed())
+                        .checked_sub(self.clock.elapsed(val.1))
                         .unwrap_or(Duration::MAX)
                 } else {
                     Duration::MAX
                 };
-                if event::poll(dur)? {
-                    let event = event::read()?;
+                if events.poll(dur)? {
+                    let event = events.read()?;
                     match event {
-                        event::Event::Mouse(e) => {
-                            self.mouse_input(e, terminal)?;
-                        }
+                        event::Event::Mouse(e) => self.mouse_input(e),
                         event::Event::Key(key) => match key.code {
                             KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
                             _ => (),
@@ -269,8 +295,8 @@ impl Game for SequenceMemory {
                 }
             }
             Mode::Results => {
-                if event::poll(Duration::from_secs(10))? {
-                    let event = event::read()?;
+                if events.poll(Duration::from_secs(10))? {
+                    let event = events.read()?;
                     if let event::Event::Key(key) = event {
                         match key.code {
                             KeyCode::Esc | KeyCode::Char('q') => {
@@ -280,6 +306,8 @@ impl Game for SequenceMemory {
                             KeyCode::Enter | KeyCode::Char('r') => {
                                 self.reset();
                             }
+                            KeyCode::Tab => self.view.tab.toggle(),
+                            KeyCode::Char('h') => self.view.toggle_histogram(),
                             _ => (),
                         }
                     }
@@ -289,8 +317,9 @@ impl Game for SequenceMemory {
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = self.layout(frame.area());
+        frame.render_widget(&*self, frame.area());
     }
 }
 
@@ -306,7 +335,7 @@ impl Filed<'_> for SequenceMemory {
     }
 
     fn get_savestate(&self) -> Self::SaveState {
-        self.savestate
+        self.savestate.clone()
     }
 }
 
@@ -352,210 +381,50 @@ impl Widget for &SequenceMemory {
                     .centered()
                     .render(constraints[1], buf);
             }
-            Mode::Clicking | Mode::Pause(_) => {
-                block.title("╡ Playing ╞").render(vert[1], buf);
-
-                let mut clicked = -1;
-                if let Some((i, instant)) = self.clicked {
-                    if (instant.elapsed().as_millis() as u64) < FADE_OUT {
-                        clicked = i as i8;
+            Mode::Watching(_) | Mode::Clicking | Mode::Pause(_) => {
+                let lit = match self.mode {
+                    Mode::Watching(step) => {
+                        block.title("╡ Watching ╞").render(vert[1], buf);
+                        Some(self.scramble[step as usize])
                     }
-                }
-
-                let rows = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),    // ---
-                        Constraint::Length(1), // Title
-                        Constraint::Length(4), // Title
-                        Constraint::Length(4), // Title
-                        Constraint::Length(4), // Title
-                        Constraint::Min(0),    // ---
-                    ])
-                    .split(main);
-
-                Paragraph::new(format!("Score: {}", self.get_score()));
-
-                let lenth = rows[2].height * 2;
-
-                let rects = [
-                    Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Min(0),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Min(0),
-                        ])
-                        .split(rows[2]),
-                    Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Min(0),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Min(0),
-                        ])
-                        .split(rows[3]),
-                    Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Min(0),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Min(0),
-                        ])
-                        .split(rows[4]),
-                ];
-
-                let mut index = 0;
-                for y in rects {
-                    for element in &y[1..4] {
-                        if index == clicked {
-                            let inner = element.inner(ratatui::layout::Margin {
-                                horizontal: 1,
-                                vertical: 1,
-                            });
-                            Block::bordered()
-                                .border_set(border::QUADRANT_INSIDE)
-                                .set_style(Style::default().fg(Color::White))
-                                .render(*element, buf);
-                            Block::new()
-                                .set_style(Style::default().bg(Color::White))
-                                .render(inner, buf);
-                        } else {
-                            Block::bordered()
-                                .border_set(border::THICK)
-                                .render(*element, buf);
-                        }
-                        index += 1;
+                    _ => {
+                        block.title("╡ Playing ╞").render(vert[1], buf);
+                        self.clicked
+                            .filter(|(_, instant)| {
+                                (self.clock.elapsed(*instant).as_millis() as u64) < FADE_OUT
+                            })
+                            .map(|(i, _)| i)
                     }
-                }
-            }
-            Mode::Watching(step) => {
-                block.title("╡ Watching ╞").render(vert[1], buf);
-
-                let rows = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),    // ---
-                        Constraint::Length(1), // Title
-                        Constraint::Length(4), // Title
-                        Constraint::Length(4), // Title
-                        Constraint::Length(4), // Title
-                        Constraint::Min(0),    // ---
-                    ])
-                    .split(main);
+                };
 
-                Paragraph::new(format!("Score: {}", self.get_score()));
-
-                let lenth = rows[2].height * 2;
-
-                let rects = [
-                    Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Min(0),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Min(0),
-                        ])
-                        .split(rows[2]),
-                    Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Min(0),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Min(0),
-                        ])
-                        .split(rows[3]),
-                    Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Min(0),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Length(lenth),
-                            Constraint::Min(0),
-                        ])
-                        .split(rows[4]),
-                ];
-
-                let mut index = 0;
-                for y in rects {
-                    for element in &y[1..4] {
-                        if index == self.scramble[step as usize] {
-                            let inner = element.inner(ratatui::layout::Margin {
-                                horizontal: 1,
-                                vertical: 1,
-                            });
-                            Block::bordered()
-                                .border_set(border::QUADRANT_INSIDE)
-                                .set_style(Style::default().fg(Color::White))
-                                .render(*element, buf);
-                            Block::new()
-                                .set_style(Style::default().bg(Color::White))
-                                .render(inner, buf);
-                        } else {
-                            Block::bordered()
-                                .border_set(border::THICK)
-                                .render(*element, buf);
-                        }
-                        index += 1;
+                for (index, element) in self.regions.iter() {
+                    if Some(index) == lit {
+                        let inner = element.inner(ratatui::layout::Margin {
+                            horizontal: 1,
+                            vertical: 1,
+                        });
+                        Block::bordered()
+                            .border_set(border::QUADRANT_INSIDE)
+                            .set_style(Style::default().fg(Color::White))
+                            .render(element, buf);
+                        Block::new()
+                            .set_style(Style::default().bg(Color::White))
+                            .render(inner, buf);
+                    } else {
+                        Block::bordered()
+                            .border_set(border::THICK)
+                            .render(element, buf);
                     }
                 }
             }
             Mode::Results => {
                 block.title("╡ Results ╞").render(vert[1], buf);
 
-                let dataset = Dataset::default()
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .cyan()
-                    .data(&[
-                        (0.0, (0.0 / 280.0)),
-                        (1.0, (50.0 / 280.0)),
-                        (2.0, (95.0 / 280.0)),
-                        (3.0, (40.0 / 280.0)),
-                        (4.0, (40.0 / 280.0)),
-                        (5.0, (66.0 / 280.0)),
-                        (6.0, (130.0 / 280.0)),
-                        (7.0, (211.0 / 280.0)),
-                        (8.0, (265.0 / 280.0)),
-                        (9.0, (265.0 / 280.0)),
-                        (10.0, (242.0 / 280.0)),
-                        (11.0, (210.0 / 280.0)),
-                        (12.0, (170.0 / 280.0)),
-                        (13.0, (130.0 / 280.0)),
-                        (14.0, (100.0 / 280.0)),
-                        (15.0, (75.0 / 280.0)),
-                        (16.0, (60.0 / 280.0)),
-                        (17.0, (40.0 / 280.0)),
-                        (18.0, (30.0 / 280.0)),
-                        (19.0, (30.0 / 280.0)),
-                        (20.0, (20.0 / 280.0)),
-                        (21.0, (17.0 / 280.0)),
-                        (22.0, (15.0 / 280.0)),
-                        (23.0, (14.0 / 280.0)),
-                        (24.0, (13.0 / 280.0)),
-                        (25.0, (10.0 / 280.0)),
-                        (26.0, (7.0 / 280.0)),
-                        (27.0, (5.0 / 280.0)),
-                        (28.0, (0.0 / 280.0)),
-                        (29.0, (0.0 / 280.0)),
-                        (30.0, (0.0 / 280.0)),
-                    ]);
-                render_graph(
-                    self.savestate.avg_score as f64,
+                render_results(
+                    self.view,
                     self.get_score() as f64,
-                    dataset,
-                    [0.0, 30.0],
+                    &self.savestate,
+                    &POPULATION,
                     main,
                     buf,
                 );
@@ -563,3 +432,69 @@ impl Widget for &SequenceMemory {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend, layout::Rect};
+
+    use super::*;
+    use crate::app::events::ScriptedEvents;
+
+    /// Draws and handles input until the game has used up `events` or quit.
+    fn play(
+        game: &mut SequenceMemory,
+        terminal: &mut Terminal<TestBackend>,
+        events: &mut ScriptedEvents,
+    ) {
+        while !game.exit && !events.is_empty() {
+            terminal.draw(|frame| game.draw(frame)).unwrap();
+            game.handle_input(events).unwrap();
+        }
+        terminal.draw(|frame| game.draw(frame)).unwrap();
+    }
+
+    fn center(rect: Rect) -> (u16, u16) {
+        (rect.x + rect.width / 2, rect.y + rect.height / 2)
+    }
+
+    #[test]
+    fn round() {
+        let clock = Clock::manual();
+        let mut game = SequenceMemory {
+            clock: clock.clone(),
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+        // The first square is shown for 600 ms before it can be clicked.
+        let mut events = ScriptedEvents::default()
+            .with_clock(clock.clone())
+            .key(KeyCode::Enter)
+            .wait(Duration::from_millis(600));
+        play(&mut game, &mut terminal, &mut events);
+        assert!(game.mode == Mode::Clicking);
+
+        let first = game.scramble[0];
+        let (x, y) = center(game.regions.get(first).unwrap());
+        let wrong = (0..9).find(|&i| i != first).unwrap();
+        let (wrong_x, wrong_y) = center(game.regions.get(wrong).unwrap());
+
+        // A pause of a second, then both squares are shown before the wrong one is clicked.
+        let mut events = ScriptedEvents::default()
+            .with_clock(clock.clone())
+            .click(x, y)
+            .wait(Duration::from_millis(1000 + 2 * 600))
+            .click(wrong_x, wrong_y);
+        play(&mut game, &mut terminal, &mut events);
+
+        assert!(game.mode == Mode::Results);
+        let attempt = game.savestate.attempts.last().unwrap();
+        assert_eq!(attempt.score, 1.0);
+        assert_eq!(attempt.duration_ms, 1000 + 3 * 600);
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("Results"));
+
+        let mut events = ScriptedEvents::default().key(KeyCode::Char('q'));
+        play(&mut game, &mut terminal, &mut events);
+        assert!(game.exit);
+    }
+}
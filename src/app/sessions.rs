@@ -0,0 +1,313 @@
+use std::{sync::OnceLock, time::Duration};
+
+use chrono::TimeDelta;
+
+use super::{savestate::Attempt, stats::Better};
+
+/// Shortest break that starts a new session, unless set with `--session-gap`.
+pub const DEFAULT_GAP: Duration = Duration::from_secs(30 * 60);
+/// Sessions shorter than this are too noisy to fit a trend to.
+pub const MIN_ATTEMPTS: usize = 6;
+/// Fewest attempts a decline has to last to count as fatigue.
+const MIN_DECLINE: usize = 4;
+
+static GAP: OnceLock<Duration> = OnceLock::new();
+
+pub fn gap() -> Duration {
+    *GAP.get_or_init(|| DEFAULT_GAP)
+}
+
+pub fn set_gap(gap: Duration) {
+    let _ = GAP.set(gap);
+}
+
+/// Splits attempts, in the order they were played, into sittings: attempts less than
+/// [`gap`] apart belong to the same one.
+pub fn split(attempts: &[Attempt]) -> Vec<&[Attempt]> {
+    let gap = TimeDelta::from_std(gap()).unwrap_or(TimeDelta::MAX);
+    attempts
+        .chunk_by(|a, b| b.timestamp - a.timestamp < gap)
+        .collect()
+}
+
+/// Part of a session an attempt falls in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    /// Scores are still improving towards the player's level, at this attempt of the usual
+    /// number it takes.
+    WarmUp { attempt: usize, of: usize },
+    /// Scores are getting worse, by this much per attempt.
+    Fatigue(f32),
+}
+
+/// Phase of the last attempt within the current session. Fatigue shows in the current session
+/// itself, but a warm-up only shows once it's over, so it's expected to last as long as it
+/// usually does.
+pub fn phase(attempts: &[Attempt], better: Better) -> Option<Phase> {
+    let sessions = split(attempts);
+    let (current, past) = sessions.split_last()?;
+    if !current.last()?.counts() {
+        return None;
+    }
+    let trend = Trend::of(current, better);
+    let index = current.iter().filter(|a| a.counts()).count() - 1;
+
+    if let Some((start, slope)) = trend.fatigue
+        && index >= start
+    {
+        return Some(Phase::Fatigue(slope));
+    }
+    let warmup = trend.warmup.max(typical_warmup(past, better));
+    (index < warmup).then_some(Phase::WarmUp {
+        attempt: index + 1,
+        of: warmup,
+    })
+}
+
+/// Median warm-up over the sessions long enough to tell, zero if there are none.
+pub fn typical_warmup(sessions: &[&[Attempt]], better: Better) -> usize {
+    let mut warmups: Vec<usize> = sessions
+        .iter()
+        .filter(|session| session.len() >= MIN_ATTEMPTS)
+        .map(|session| Trend::of(session, better).warmup)
+        .collect();
+    warmups.sort_unstable();
+    warmups.get(warmups.len() / 2).copied().unwrap_or_default()
+}
+
+/// How scores moved over one session.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Trend {
+    /// Number of attempts at the start that were still improving.
+    pub warmup: usize,
+    /// Attempt from which scores got steadily worse, and by how much per attempt, in the units
+    /// of the game.
+    pub fatigue: Option<(usize, f32)>,
+}
+
+impl Trend {
+    /// Fits a trend to the attempts of one session that count. Warm-up is found by splitting the
+    /// session where an improving line followed by a second line fits best, fatigue by splitting
+    /// the rest where a flat level followed by a worsening line fits best. Either is only
+    /// reported when it stands out from the noise.
+    pub fn of(session: &[Attempt], better: Better) -> Self {
+        let session: Vec<&Attempt> = session.iter().filter(|a| a.counts()).collect();
+        if session.len() < MIN_ATTEMPTS {
+            return Self::default();
+        }
+
+        // Higher is better from here on, whatever the game.
+        let sign = match better {
+            Better::Higher => 1.0,
+            Better::Lower => -1.0,
+        };
+        let scores: Vec<f64> = session.iter().map(|a| sign * a.score as f64).collect();
+
+        let warmup = Trend::warmup(&scores);
+        let fatigue = Trend::fatigue(&scores[warmup..])
+            .map(|(start, slope)| (warmup + start, (sign * slope) as f32));
+        Self { warmup, fatigue }
+    }
+
+    fn warmup(scores: &[f64]) -> usize {
+        let whole = Fit::new(scores).sse;
+        let best = (2..=scores.len() / 2)
+            .filter_map(|k| {
+                let (before, after) = scores.split_at(k);
+                let (first, rest) = (Fit::new(before), Fit::new(after));
+                let gain = mean(after) - mean(before);
+                (first.slope > 0.0 && gain > std_dev(after) / 2.0)
+                    .then_some((k, first.sse + rest.sse))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match best {
+            Some((k, sse)) if sse < 0.75 * whole => k,
+            _ => 0,
+        }
+    }
+
+    /// Start of the decline within `scores` and its slope, if there is one.
+    fn fatigue(scores: &[f64]) -> Option<(usize, f64)> {
+        if scores.len() < MIN_DECLINE {
+            return None;
+        }
+
+        let (start, fit, sse) = (0..=scores.len() - MIN_DECLINE)
+            .map(|s| {
+                let (level, decline) = scores.split_at(s);
+                let flat = level.iter().map(|x| (x - mean(level)).powi(2)).sum::<f64>();
+                let fit = Fit::new(decline);
+                (s, fit, flat + fit.sse)
+            })
+            .filter(|(_, fit, _)| fit.slope < 0.0)
+            .min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+        let drop = -fit.slope * (scores.len() - 1 - start) as f64;
+        let noise = (sse / scores.len() as f64).sqrt();
+        (drop > 1.5 * noise).then_some((start, fit.slope))
+    }
+}
+
+/// Least squares line through scores against their index.
+#[derive(Debug, Clone, Copy)]
+struct Fit {
+    slope: f64,
+    /// Sum of squared residuals.
+    sse: f64,
+}
+
+impl Fit {
+    fn new(scores: &[f64]) -> Self {
+        let n = scores.len() as f64;
+        let (mean_x, mean_y) = ((n - 1.0) / 2.0, mean(scores));
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for (x, y) in scores.iter().enumerate() {
+            sxy += (x as f64 - mean_x) * (y - mean_y);
+            sxx += (x as f64 - mean_x).powi(2);
+        }
+        let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+
+        let sse = scores
+            .iter()
+            .enumerate()
+            .map(|(x, y)| (y - mean_y - slope * (x as f64 - mean_x)).powi(2))
+            .sum();
+        Self { slope, sse }
+    }
+}
+
+fn mean(scores: &[f64]) -> f64 {
+    if scores.is_empty() {
+        return 0.0;
+    }
+    scores.iter().sum::<f64>() / scores.len() as f64
+}
+
+fn std_dev(scores: &[f64]) -> f64 {
+    let mean = mean(scores);
+    (scores.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / scores.len().max(1) as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Local, TimeZone};
+
+    use super::*;
+
+    fn start() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 2, 18, 0, 0).unwrap()
+    }
+
+    /// One session of `scores`, a minute apart.
+    fn session(scores: &[f32]) -> Vec<Attempt> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(i, &score)| attempt(score, start() + TimeDelta::minutes(i as i64)))
+            .collect()
+    }
+
+    fn attempt(score: f32, timestamp: DateTime<Local>) -> Attempt {
+        Attempt {
+            score,
+            timestamp,
+            variant: String::new(),
+            duration_ms: 0,
+            seed: None,
+            trials: Vec::new(),
+            flag: None,
+        }
+    }
+
+    const IMPROVES: [f32; 12] = [
+        10.0, 20.0, 30.0, 40.0, 50.0, 49.0, 51.0, 50.0, 49.0, 51.0, 50.0, 50.0,
+    ];
+    const FLAT: [f32; 8] = [50.0, 51.0, 49.0, 50.0, 50.0, 51.0, 49.0, 50.0];
+    const DECLINES: [f32; 9] = [50.0, 51.0, 49.0, 50.0, 50.0, 40.0, 30.0, 20.0, 10.0];
+
+    #[test]
+    fn warmup_lasts_until_the_plateau() {
+        assert_eq!(
+            Trend::of(&session(&IMPROVES), Better::Higher),
+            Trend {
+                warmup: 5,
+                fatigue: None
+            }
+        );
+
+        // Times that fall to a plateau are a warm-up just the same.
+        let times = IMPROVES.map(|score| 500.0 - score);
+        assert_eq!(Trend::of(&session(&times), Better::Lower).warmup, 5);
+    }
+
+    #[test]
+    fn a_flat_session_has_no_trend() {
+        assert_eq!(Trend::of(&session(&FLAT), Better::Higher), Trend::default());
+        assert_eq!(Trend::of(&session(&FLAT), Better::Lower), Trend::default());
+    }
+
+    #[test]
+    fn fatigue_starts_where_scores_decline() {
+        assert_eq!(
+            Trend::of(&session(&DECLINES), Better::Higher),
+            Trend {
+                warmup: 0,
+                fatigue: Some((4, -10.0))
+            }
+        );
+
+        // Times that rise get worse by as many milliseconds per attempt.
+        let times = DECLINES.map(|score| 500.0 - score);
+        assert_eq!(
+            Trend::of(&session(&times), Better::Lower).fatigue,
+            Some((4, 10.0))
+        );
+    }
+
+    #[test]
+    fn short_sessions_are_too_noisy() {
+        assert_eq!(
+            Trend::of(&session(&DECLINES[4..]), Better::Higher),
+            Trend::default()
+        );
+    }
+
+    #[test]
+    fn phase_of_the_last_attempt() {
+        let declining = session(&DECLINES);
+        assert_eq!(
+            phase(&declining, Better::Higher),
+            Some(Phase::Fatigue(-10.0))
+        );
+
+        // Two attempts into a session after one that took five to warm up.
+        let mut attempts = session(&IMPROVES);
+        let later = start() + TimeDelta::days(1);
+        attempts.push(attempt(10.0, later));
+        attempts.push(attempt(20.0, later + TimeDelta::minutes(1)));
+        assert_eq!(
+            phase(&attempts, Better::Higher),
+            Some(Phase::WarmUp { attempt: 2, of: 5 })
+        );
+
+        assert_eq!(phase(&session(&FLAT), Better::Higher), None);
+    }
+
+    #[test]
+    fn sessions_split_at_the_gap() {
+        let gap = TimeDelta::from_std(gap()).unwrap();
+        let attempts = [
+            attempt(1.0, start()),
+            attempt(2.0, start() + gap - TimeDelta::seconds(1)),
+            attempt(3.0, start() + gap * 2 - TimeDelta::seconds(1)),
+        ];
+
+        let sessions = split(&attempts);
+        let scores: Vec<Vec<f32>> = sessions
+            .iter()
+            .map(|session| session.iter().map(|a| a.score).collect())
+            .collect();
+        assert_eq!(scores, [vec![1.0, 2.0], vec![3.0]]);
+    }
+}
@@ -0,0 +1,148 @@
+use serde::Serialize;
+
+/// Number of attempts the rolling average covers.
+pub const ROLLING: usize = 10;
+
+/// Which way scores of a game improve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Better {
+    #[default]
+    Higher,
+    /// Times, where less is better.
+    Lower,
+}
+
+impl Better {
+    /// Whether `a` is a better score than `b`.
+    pub fn is_better(self, a: f32, b: f32) -> bool {
+        match self {
+            Better::Higher => a > b,
+            Better::Lower => a < b,
+        }
+    }
+}
+
+/// Descriptive statistics over a series of scores.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct Stats {
+    pub count: usize,
+    pub mean: f32,
+    /// Sample standard deviation, zero for a single score.
+    pub std_dev: f32,
+    pub median: f32,
+    pub best: f32,
+    pub worst: f32,
+    /// Mean of the last [`ROLLING`] scores.
+    pub recent: f32,
+}
+
+impl Stats {
+    /// Statistics of the scores in the order they were played, or `None` if there are none.
+    pub fn new(scores: &[f32], better: Better) -> Option<Self> {
+        let (&first, _) = scores.split_first()?;
+
+        // Welford's method, which stays accurate where summing squares would not.
+        let (mut mean, mut m2) = (0.0f64, 0.0f64);
+        let (mut best, mut worst) = (first, first);
+        for (n, &score) in scores.iter().enumerate() {
+            let delta = score as f64 - mean;
+            mean += delta / (n + 1) as f64;
+            m2 += delta * (score as f64 - mean);
+
+            if better.is_better(score, best) {
+                best = score;
+            }
+            if better.is_better(worst, score) {
+                worst = score;
+            }
+        }
+
+        let mut sorted = scores.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+
+        let recent = &scores[scores.len().saturating_sub(ROLLING)..];
+
+        Some(Self {
+            count: scores.len(),
+            mean: mean as f32,
+            std_dev: if scores.len() > 1 {
+                (m2 / (scores.len() - 1) as f64).sqrt() as f32
+            } else {
+                0.0
+            },
+            median,
+            best,
+            worst,
+            recent: recent.iter().sum::<f32>() / recent.len() as f32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_and_spread() {
+        let stats = Stats::new(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], Better::Higher).unwrap();
+        assert_eq!(stats.count, 8);
+        assert_eq!(stats.mean, 5.0);
+        // Sum of squared deviations is 32, over 7 degrees of freedom.
+        assert!((stats.std_dev - (32.0f32 / 7.0).sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        let odd = Stats::new(&[9.0, 1.0, 5.0], Better::Higher).unwrap();
+        assert_eq!(odd.median, 5.0);
+        let even = Stats::new(&[9.0, 1.0, 5.0, 3.0], Better::Higher).unwrap();
+        assert_eq!(even.median, 4.0);
+    }
+
+    #[test]
+    fn rolling_average_covers_the_last_scores() {
+        let scores: Vec<f32> = (1..=15).map(|n| n as f32).collect();
+        let stats = Stats::new(&scores, Better::Higher).unwrap();
+        // 6 through 15.
+        assert_eq!(stats.recent, 10.5);
+
+        let short = Stats::new(&[1.0, 2.0, 3.0], Better::Higher).unwrap();
+        assert_eq!(short.recent, 2.0);
+    }
+
+    #[test]
+    fn best_and_worst_follow_the_direction() {
+        let scores = [250.0, 180.0, 320.0, 200.0];
+
+        let higher = Stats::new(&scores, Better::Higher).unwrap();
+        assert_eq!((higher.best, higher.worst), (320.0, 180.0));
+
+        let lower = Stats::new(&scores, Better::Lower).unwrap();
+        assert_eq!((lower.best, lower.worst), (180.0, 320.0));
+    }
+
+    #[test]
+    fn a_single_score() {
+        let stats = Stats::new(&[42.0], Better::Lower).unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                count: 1,
+                mean: 42.0,
+                std_dev: 0.0,
+                median: 42.0,
+                best: 42.0,
+                worst: 42.0,
+                recent: 42.0,
+            }
+        );
+        assert_eq!(Stats::new(&[], Better::Higher), None);
+    }
+}
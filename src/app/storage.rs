@@ -0,0 +1,142 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    iter,
+    path::Path,
+};
+
+use serde::de::DeserializeOwned;
+
+use super::migration;
+
+/// Number of earlier versions of a save file kept next to it, as `<file>.1` (the newest) up to
+/// `<file>.BACKUPS`.
+pub const BACKUPS: usize = 3;
+
+/// Path of the `n`th newest backup of `file`.
+pub fn backup(file: &str, n: usize) -> String {
+    format!("{file}.{n}")
+}
+
+/// Reads a save file, upgrading it from older versions. `Ok(None)` means there is no such file,
+/// an error that there is one but it can't be used.
+pub fn read<S: DeserializeOwned>(file: &str) -> Result<Option<S>, String> {
+    let contents = match fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    let value = migration::upgrade(value)?;
+    serde_json::from_value(value)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// Replaces `file` with `contents` so that a crash leaves either the old or the new version, but
+/// never half of one: the contents go to a temporary file first, which is synced to disk and
+/// renamed over `file`. The version it replaces becomes the newest backup.
+pub fn write(file: &str, contents: &str) -> io::Result<()> {
+    let tmp = format!("{file}.tmp");
+    // Whatever step fails, the temporary file is of no use to anyone.
+    replace(file, &tmp, contents).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })?;
+
+    // The rename only survives a crash once the directory is synced too. Not every platform can
+    // open a directory for that, so it's best effort.
+    if let Some(dir) = Path::new(file).parent()
+        && let Ok(dir) = File::open(dir)
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+fn replace(file: &str, tmp: &str, contents: &str) -> io::Result<()> {
+    let mut out = File::create(tmp)?;
+    out.write_all(contents.as_bytes())?;
+    out.sync_all()?;
+    drop(out);
+
+    if Path::new(file).exists() {
+        rotate(file)?;
+    }
+    fs::rename(tmp, file)
+}
+
+/// Deletes `file` and its backups, those that exist.
+pub fn remove(file: &str) -> io::Result<()> {
+    for path in versions(file) {
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Whether `file` or any of its backups exists.
+pub fn exists(file: &str) -> bool {
+    versions(file).any(|path| Path::new(&path).exists())
+}
+
+/// `file` followed by its backups, newest first.
+fn versions(file: &str) -> impl Iterator<Item = String> + '_ {
+    iter::once(file.to_owned()).chain((1..=BACKUPS).map(|n| backup(file, n)))
+}
+
+/// Shifts every backup of `file` one place back, dropping the oldest, and copies `file` in as
+/// the newest.
+fn rotate(file: &str) -> io::Result<()> {
+    for n in (1..BACKUPS).rev() {
+        let from = backup(file, n);
+        if Path::new(&from).exists() {
+            fs::rename(&from, backup(file, n + 1))?;
+        }
+    }
+    fs::copy(file, backup(file, 1))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file in the data directory of the test, which is created empty.
+    fn file(name: &str) -> String {
+        let dir = crate::app::data_dir().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name).to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn writes_keep_backups_until_removed() {
+        let file = file("Backups.json");
+        for n in 0..=BACKUPS {
+            write(&file, &n.to_string()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&file).unwrap(), BACKUPS.to_string());
+        for n in 1..=BACKUPS {
+            assert_eq!(
+                fs::read_to_string(backup(&file, n)).unwrap(),
+                (BACKUPS - n).to_string()
+            );
+        }
+
+        remove(&file).unwrap();
+        assert!(!exists(&file));
+        remove(&file).unwrap();
+    }
+
+    #[test]
+    fn failed_write_leaves_no_temporary_file() {
+        // A directory where the file should be can't be backed up or replaced.
+        let file = file("Directory.json");
+        fs::create_dir_all(&file).unwrap();
+
+        assert!(write(&file, "{}").is_err());
+        assert!(!Path::new(&format!("{file}.tmp")).exists());
+    }
+}
@@ -2,24 +2,35 @@ mod mode;
 mod texts;
 
 use mode::Mode;
-use rand::{Rng, rng};
+use rand::Rng;
 use ratatui::{
-    Frame,
+    Frame, Terminal,
+    backend::Backend,
     crossterm::event::{self, KeyCode, KeyEvent, MouseEventKind},
     layout::{Constraint, Direction, Layout, Margin},
     style::{Color, Stylize},
-    symbols::{Marker, border},
+    symbols::border,
     text::{Line, Span},
-    widgets::{Block, Dataset, GraphType, Paragraph, Widget},
+    widgets::{Block, Paragraph, Widget},
+};
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
 };
-use std::time::{Duration, Instant};
 use texts::TEXTS;
 
-use super::{Filed, Game, render_graph, savestate::SaveState};
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, population::Population,
+    render_results, rng::SessionRng, savestate::SaveState,
+};
 
 const FILE_NAME: &str = "TypingTest";
 
-#[derive(Default, Debug, Clone)]
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<TypingTest>(include_str!("../../data/population/TypingTest.csv"))
+});
+
+#[derive(Debug, Clone)]
 pub struct TypingTest {
     exit: bool,
 
@@ -28,13 +39,35 @@ pub struct TypingTest {
     text: String,
     text_index: usize,
     savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
     mode: Mode,
 }
 
+impl Default for TypingTest {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            wpm: None,
+            instant: None,
+            text: String::new(),
+            text_index: 0,
+            savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng: SessionRng::attempt(FILE_NAME),
+            view: ResultsView::default(),
+            mode: Mode::default(),
+        }
+    }
+}
+
 impl TypingTest {
     fn reset(&mut self) {
         let new = Self {
-            savestate: self.savestate,
+            savestate: std::mem::take(&mut self.savestate),
+            clock: self.clock.clone(),
+            view: self.view,
             ..Default::default()
         };
         *self = new;
@@ -45,6 +78,8 @@ impl TypingTest {
                 KeyCode::Char(' ') | KeyCode::Enter => self.play(),
                 KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
                 KeyCode::Char('r') => self.reset(),
+                KeyCode::Tab if self.mode == Mode::Results => self.view.tab.toggle(),
+                KeyCode::Char('h') if self.mode == Mode::Results => self.view.toggle_histogram(),
                 _ => (),
             },
             Mode::Playing => match key.code {
@@ -66,7 +101,7 @@ impl TypingTest {
     }
     fn add_ch(&mut self, c: char) {
         if self.text.is_empty() {
-            self.instant = Some(Instant::now());
+            self.instant = Some(self.clock.now());
         }
         if self.text.len() < TEXTS[self.text_index].len() {
             let last = self
@@ -85,16 +120,20 @@ impl TypingTest {
     }
 
     fn play(&mut self) {
+        // Another text starts a new attempt, which gets a seed of its own.
+        if self.mode == Mode::Results {
+            self.reset();
+        }
         self.mode = Mode::Playing;
-        self.text_index = rng().random_range(0..TEXTS.len());
+        self.text_index = self.rng.random_range(0..TEXTS.len());
         self.text = String::new();
-        self.instant = Some(Instant::now());
+        self.instant = Some(self.clock.now());
     }
 
     fn get_wpm(&self) -> Option<f32> {
         Some(
             (self.text.len() as f32 * self.get_acc() / 5.0)
-                / (self.instant?.elapsed().as_millis() as f32 / 1000.0)
+                / (self.clock.elapsed(self.instant?).as_millis() as f32 / 1000.0)
                 * 60.0,
         )
     }
@@ -111,7 +150,7 @@ impl TypingTest {
         matches as f32 / total as f32
     }
 
-    fn get_text(&self) -> Line {
+    fn get_text(&self) -> Line<'_> {
         let mut text = Line::default();
         let mut iterator = self.text.chars();
 
@@ -133,26 +172,40 @@ impl TypingTest {
     fn results(&mut self) {
         self.mode = Mode::Results;
         self.wpm = self.get_wpm();
-        self.savestate.update(self.wpm.unwrap());
+        self.savestate.update(
+            self.wpm.unwrap(),
+            &format!("text {}", self.text_index),
+            self.instant
+                .map(|i| self.clock.elapsed(i))
+                .unwrap_or_default(),
+            self.rng.seed(),
+        );
     }
 }
 
 impl Game for TypingTest {
-    fn run(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
+    fn run<B: Backend>(
+        terminal: &mut Terminal<B>,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<()> {
         let mut game = Self::load().unwrap_or_default();
+        game.clock = events.clock();
 
         while !game.exit {
             terminal.draw(|frame| game.draw(frame))?;
-            game.handle_input(terminal)?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
         }
 
         game.save();
         Ok(())
     }
 
-    fn handle_input(&mut self, _: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
-        if event::poll(Duration::MAX)? {
-            match event::read()? {
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> std::io::Result<()> {
+        if events.poll(Duration::MAX)? {
+            match events.read()? {
                 event::Event::Key(key) => self.key_event(key),
                 event::Event::Mouse(mouse) => {
                     if let Mode::Playing = self.mode {
@@ -167,8 +220,8 @@ impl Game for TypingTest {
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        frame.render_widget(&*self, frame.area());
     }
 }
 
@@ -177,7 +230,7 @@ impl Filed<'_> for TypingTest {
     const NAME: &'static str = FILE_NAME;
 
     fn get_savestate(&self) -> Self::SaveState {
-        self.savestate
+        self.savestate.clone()
     }
     fn from_savestate(savestate: Self::SaveState) -> Self {
         Self {
@@ -237,39 +290,11 @@ impl Widget for &TypingTest {
             Mode::Results => {
                 block.title("╡ Results ╞").render(vert[1], buf);
 
-                let dataset = Dataset::default()
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .cyan()
-                    .data(&[
-                        (0.0, (30.0 / 300.0)),
-                        (10.0, (70.0 / 300.0)),
-                        (20.0, (160.0 / 300.0)),
-                        (30.0, (233.0 / 300.0)),
-                        (40.0, (275.0 / 300.0)),
-                        (50.0, (247.0 / 300.0)),
-                        (60.0, (213.0 / 300.0)),
-                        (70.0, (160.0 / 300.0)),
-                        (80.0, (131.0 / 300.0)),
-                        (90.0, (75.0 / 300.0)),
-                        (100.0, (57.0 / 300.0)),
-                        (110.0, (27.0 / 300.0)),
-                        (120.0, (17.0 / 300.0)),
-                        (130.0, (5.0 / 300.0)),
-                        (140.0, (0.0 / 300.0)),
-                        (150.0, (0.0 / 300.0)),
-                        (160.0, (0.0 / 300.0)),
-                        (170.0, (0.0 / 300.0)),
-                        (180.0, (0.0 / 300.0)),
-                        (190.0, (0.0 / 300.0)),
-                        (200.0, (0.0 / 300.0)),
-                    ]);
-
-                render_graph(
-                    self.savestate.avg_score as f64,
+                render_results(
+                    self.view,
                     self.wpm.unwrap() as f64,
-                    dataset,
-                    [0.0, 200.0],
+                    &self.savestate,
+                    &POPULATION,
                     main,
                     buf,
                 );
@@ -277,3 +302,85 @@ impl Widget for &TypingTest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+    use crate::app::events::ScriptedEvents;
+
+    /// Draws and handles input until the game has used up `events` or quit.
+    fn play(
+        game: &mut TypingTest,
+        terminal: &mut Terminal<TestBackend>,
+        events: &mut ScriptedEvents,
+    ) {
+        while !game.exit && !events.is_empty() {
+            terminal.draw(|frame| game.draw(frame)).unwrap();
+            game.handle_input(events).unwrap();
+        }
+        terminal.draw(|frame| game.draw(frame)).unwrap();
+    }
+
+    #[test]
+    fn typing_the_text() {
+        let mut game = TypingTest::default();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+        play(
+            &mut game,
+            &mut terminal,
+            &mut ScriptedEvents::default().key(KeyCode::Enter),
+        );
+        assert_eq!(game.mode, Mode::Playing);
+
+        let text = TEXTS[game.text_index];
+        play(
+            &mut game,
+            &mut terminal,
+            &mut ScriptedEvents::default().keys(text.chars().map(KeyCode::Char)),
+        );
+        assert_eq!(game.mode, Mode::Results);
+        assert_eq!(game.text, text);
+        assert_eq!(game.get_acc(), 1.0);
+        assert_eq!(game.savestate.attempts.len(), 1);
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("Results"));
+    }
+
+    #[test]
+    fn wpm() {
+        let events = ScriptedEvents::default().with_clock(Clock::manual());
+        let mut game = TypingTest {
+            clock: events.clock(),
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+        play(
+            &mut game,
+            &mut terminal,
+            &mut events.clone().key(KeyCode::Enter),
+        );
+        // The time runs from the first character to the last.
+        let text: Vec<char> = TEXTS[game.text_index].chars().take(250).collect();
+        let mut events = events
+            .keys(text[..249].iter().copied().map(KeyCode::Char))
+            .wait(Duration::from_secs(60))
+            .key(KeyCode::Char(text[249]));
+        play(&mut game, &mut terminal, &mut events);
+
+        assert_eq!(game.text.len(), 250);
+        assert_eq!(game.get_wpm(), Some(50.0));
+    }
+
+    #[test]
+    fn run_saves_on_the_way_out() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut events = ScriptedEvents::default().key(KeyCode::Char('q'));
+
+        TypingTest::run(&mut terminal, &mut events).unwrap();
+        assert!(events.is_empty());
+        assert!(TypingTest::load_savestate().is_some());
+    }
+}
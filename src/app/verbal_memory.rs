@@ -1,28 +1,47 @@
 mod mode;
 mod words;
 
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
 use words::WORDS;
 
 use mode::Mode;
 
-use rand::{Rng, rng};
+use rand::Rng;
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame, Terminal,
+    backend::Backend,
     crossterm::event::{self, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
     layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Style, Stylize},
-    symbols::{Marker, border},
+    symbols::border,
     text::{Line, Span},
-    widgets::{Block, Dataset, GraphType, Paragraph, Widget},
+    widgets::{Block, Paragraph, Widget},
 };
 
-use super::{Filed, Game, render_graph, savestate::SaveState};
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, population::Population,
+    regions::Regions, render_results, rng::SessionRng, savestate::SaveState,
+};
 
 const FILE_NAME: &str = "VerbalMemory";
+const VARIANT: &str = "standard";
 const CHANCE: u32 = 5;
 const LIVES: u32 = 3;
 
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<VerbalMemory>(include_str!("../../data/population/VerbalMemory.csv"))
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    Seen,
+    New,
+}
+
 #[derive(Debug, Clone)]
 pub struct VerbalMemory {
     exit: bool,
@@ -31,8 +50,13 @@ pub struct VerbalMemory {
 
     mode: Mode,
     current: usize,
-    set: HashSet<usize>,
+    set: BTreeSet<usize>,
+    start: Option<Instant>,
     savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
+    regions: Regions<Region>,
 }
 
 impl Default for VerbalMemory {
@@ -43,8 +67,13 @@ impl Default for VerbalMemory {
             lives: LIVES,
             mode: Mode::default(),
             current: 0,
-            set: HashSet::new(),
+            set: BTreeSet::new(),
+            start: None,
             savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng: SessionRng::attempt(FILE_NAME),
+            view: ResultsView::default(),
+            regions: Regions::default(),
         }
     }
 }
@@ -52,7 +81,9 @@ impl Default for VerbalMemory {
 impl VerbalMemory {
     fn reset(&mut self) {
         let new = Self {
-            savestate: self.savestate,
+            savestate: std::mem::take(&mut self.savestate),
+            clock: self.clock.clone(),
+            view: self.view,
             ..Default::default()
         };
         *self = new;
@@ -63,10 +94,7 @@ impl VerbalMemory {
             Mode::Waiting => match key.code {
                 KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
                 KeyCode::Char('r') => self.reset(),
-                KeyCode::Enter | KeyCode::Char(' ') => {
-                    self.mode = Mode::Playing;
-                    self.new_word()
-                }
+                KeyCode::Enter | KeyCode::Char(' ') => self.play(),
                 _ => (),
             },
             Mode::Playing => match key.code {
@@ -79,17 +107,18 @@ impl VerbalMemory {
             Mode::Results => match key.code {
                 KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
                 KeyCode::Enter | KeyCode::Char('r') => self.reset(),
+                KeyCode::Tab => self.view.tab.toggle(),
+                KeyCode::Char('h') => self.view.toggle_histogram(),
                 _ => (),
             },
         }
     }
 
-    fn mouse_event(&mut self, mouse: MouseEvent, terminal: &mut DefaultTerminal) {
+    fn mouse_event(&mut self, mouse: MouseEvent) {
         match self.mode {
             Mode::Waiting => {
                 if let MouseEventKind::Down(_) = mouse.kind {
-                    self.mode = Mode::Playing;
-                    self.new_word();
+                    self.play();
                 }
             }
             Mode::Playing => {
@@ -98,49 +127,10 @@ impl VerbalMemory {
                     return;
                 }
 
-                let main = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Length(3), Constraint::Min(0)])
-                    .split(terminal.get_frame().area())[1]
-                    .inner(Margin {
-                        horizontal: 1,
-                        vertical: 1,
-                    });
-
-                let constraints = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(1),
-                        Constraint::Min(0),
-                    ])
-                    .split(main);
-
-                let buttons_vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Percentage(20),
-                        Constraint::Length(3),
-                        Constraint::Min(0),
-                    ])
-                    .split(constraints[2])[1];
-
-                let buttons = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(6),
-                        Constraint::Percentage(20),
-                        Constraint::Length(6),
-                        Constraint::Min(0),
-                    ])
-                    .split(buttons_vert);
-
-                let mouse_rect = Rect::new(mouse.column, mouse.row, 1, 1);
-                if mouse_rect.intersects(buttons[1]) {
-                    self.submit_seen();
-                } else if mouse_rect.intersects(buttons[3]) {
-                    self.submit_new();
+                match self.regions.at(mouse.column, mouse.row) {
+                    Some(Region::Seen) => self.submit_seen(),
+                    Some(Region::New) => self.submit_new(),
+                    None => (),
                 }
             }
             Mode::Results => {
@@ -151,8 +141,61 @@ impl VerbalMemory {
         }
     }
 
+    /// Lays out the SEEN and NEW buttons.
+    fn layout(&self, area: Rect) -> Regions<Region> {
+        if self.mode != Mode::Playing {
+            return Regions::default();
+        }
+
+        let main = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area)[1]
+            .inner(Margin {
+                horizontal: 1,
+                vertical: 1,
+            });
+
+        let constraints = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ])
+            .split(main);
+
+        let buttons_vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(20),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .split(constraints[2])[1];
+
+        let buttons = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(6),
+                Constraint::Percentage(20),
+                Constraint::Length(6),
+                Constraint::Min(0),
+            ])
+            .split(buttons_vert);
+
+        Regions::from_iter([(Region::Seen, buttons[1]), (Region::New, buttons[3])])
+    }
+
+    fn play(&mut self) {
+        self.mode = Mode::Playing;
+        self.start = Some(self.clock.now());
+        self.new_word();
+    }
+
     fn new_word(&mut self) {
-        let mut rng = rng();
+        let rng = &mut self.rng;
         if rng.random_range(0..CHANCE) == 1 && !self.set.is_empty() {
             self.current = *self
                 .set
@@ -169,7 +212,14 @@ impl VerbalMemory {
             self.lives -= 1;
         } else {
             self.mode = Mode::Results;
-            self.savestate.update(self.score as f32);
+            self.savestate.update(
+                self.score as f32,
+                VARIANT,
+                self.start
+                    .map(|s| self.clock.elapsed(s))
+                    .unwrap_or_default(),
+                self.rng.seed(),
+            );
         }
     }
 
@@ -194,23 +244,30 @@ impl VerbalMemory {
 }
 
 impl Game for VerbalMemory {
-    fn run(terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
+    fn run<B: Backend>(
+        terminal: &mut Terminal<B>,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<()> {
         let mut game = Self::load().unwrap_or_default();
+        game.clock = events.clock();
 
         while !game.exit {
             terminal.draw(|frame| game.draw(frame))?;
-            game.handle_input(terminal)?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
         }
 
         game.save();
         Ok(())
     }
 
-    fn handle_input(&mut self, terminal: &mut ratatui::DefaultTerminal) -> std::io::Result<()> {
-        if event::poll(Duration::MAX)? {
-            match event::read()? {
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> std::io::Result<()> {
+        if events.poll(Duration::MAX)? {
+            match events.read()? {
                 event::Event::Key(key) => self.key_event(key),
-                event::Event::Mouse(mouse) => self.mouse_event(mouse, terminal),
+                event::Event::Mouse(mouse) => self.mouse_event(mouse),
                 _ => (),
             }
         }
@@ -218,8 +275,9 @@ impl Game for VerbalMemory {
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = self.layout(frame.area());
+        frame.render_widget(&*self, frame.area());
     }
 }
 
@@ -228,7 +286,7 @@ impl Filed<'_> for VerbalMemory {
     const NAME: &'static str = FILE_NAME;
 
     fn get_savestate(&self) -> Self::SaveState {
-        self.savestate
+        self.savestate.clone()
     }
     fn from_savestate(savestate: Self::SaveState) -> Self {
         Self {
@@ -319,75 +377,27 @@ impl Widget for &VerbalMemory {
                     .centered()
                     .render(constraints[1], buf);
 
-                let buttons_vert = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Percentage(20),
-                        Constraint::Length(3),
-                        Constraint::Min(0),
-                    ])
-                    .split(constraints[2])[1];
-
-                let buttons = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Min(0),
-                        Constraint::Length(6),
-                        Constraint::Percentage(20),
-                        Constraint::Length(6),
-                        Constraint::Min(0),
-                    ])
-                    .split(buttons_vert);
-
-                Paragraph::new(Line::from(vec![
-                    Span::styled("S", Style::default().underlined()),
-                    Span::raw("EEN"),
-                ]))
-                .block(Block::bordered().border_set(border::DOUBLE))
-                .render(buttons[1], buf);
-
-                Paragraph::new(Line::from(vec![
-                    Span::styled("N", Style::default().underlined()),
-                    Span::raw("EW"),
-                ]))
-                .block(Block::bordered().border_set(border::DOUBLE))
-                .render(buttons[3], buf);
+                for (region, rect) in self.regions.iter() {
+                    let (key, rest) = match region {
+                        Region::Seen => ("S", "EEN"),
+                        Region::New => ("N", "EW"),
+                    };
+                    Paragraph::new(Line::from(vec![
+                        Span::styled(key, Style::default().underlined()),
+                        Span::raw(rest),
+                    ]))
+                    .block(Block::bordered().border_set(border::DOUBLE))
+                    .render(rect, buf);
+                }
             }
             Mode::Results => {
                 block.title("╡ Results ╞").render(vert[1], buf);
 
-                let dataset = Dataset::default()
-                    .marker(Marker::Braille)
-                    .graph_type(GraphType::Line)
-                    .cyan()
-                    .data(&[
-                        (0.0, (220.0 / 270.0)),
-                        (10.0, (180.0 / 270.0)),
-                        (20.0, (233.0 / 270.0)),
-                        (30.0, (247.0 / 270.0)),
-                        (40.0, (223.0 / 270.0)),
-                        (50.0, (180.0 / 270.0)),
-                        (60.0, (142.0 / 270.0)),
-                        (70.0, (102.0 / 270.0)),
-                        (80.0, (78.0 / 270.0)),
-                        (90.0, (60.0 / 270.0)),
-                        (100.0, (47.0 / 270.0)),
-                        (110.0, (32.0 / 270.0)),
-                        (120.0, (25.0 / 270.0)),
-                        (130.0, (20.0 / 270.0)),
-                        (140.0, (16.0 / 270.0)),
-                        (150.0, (5.0 / 270.0)),
-                        (160.0, (5.0 / 270.0)),
-                        (170.0, (5.0 / 270.0)),
-                        (180.0, (0.0 / 270.0)),
-                        (190.0, (0.0 / 270.0)),
-                    ]);
-
-                render_graph(
-                    self.savestate.avg_score as f64,
+                render_results(
+                    self.view,
                     self.score as f64,
-                    dataset,
-                    [0.0, 190.0],
+                    &self.savestate,
+                    &POPULATION,
                     main,
                     buf,
                 );
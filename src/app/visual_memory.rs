@@ -0,0 +1,552 @@
+mod mode;
+
+use mode::Mode;
+use rand::seq::index::sample;
+use ratatui::{
+    Frame, Terminal,
+    backend::Backend,
+    crossterm::event::{self, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style, Styled, Stylize},
+    symbols::border,
+    text::Span,
+    widgets::{Block, Paragraph, Widget},
+};
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use super::{
+    Filed, Game, ResultsView, clock::Clock, events::EventSource, population::Population,
+    regions::Regions, render_results, rng::SessionRng, savestate::SaveState,
+};
+
+const FILE_NAME: &str = "VisualMemory";
+const VARIANT: &str = "standard";
+const FLASH: u64 = 1000;
+const PAUSE: u64 = 800;
+const LIVES: u32 = 3;
+const MAX_CELL: u16 = 4;
+
+pub static POPULATION: LazyLock<Population> = LazyLock::new(|| {
+    Population::load::<VisualMemory>(include_str!("../../data/population/VisualMemory.csv"))
+});
+
+#[derive(Debug, Clone)]
+pub struct VisualMemory {
+    exit: bool,
+    level: u32,
+    lives: u32,
+
+    targets: Vec<bool>,
+    found: Vec<bool>,
+    missed: Vec<bool>,
+    cursor: usize,
+    start: Option<Instant>,
+    mode: Mode,
+    savestate: SaveState,
+    clock: Clock,
+    rng: SessionRng,
+    view: ResultsView,
+    regions: Regions<usize>,
+}
+
+impl Default for VisualMemory {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            level: 1,
+            lives: LIVES,
+            targets: Vec::new(),
+            found: Vec::new(),
+            missed: Vec::new(),
+            cursor: 0,
+            start: None,
+            mode: Mode::default(),
+            savestate: SaveState::default(),
+            clock: Clock::default(),
+            rng: SessionRng::attempt(FILE_NAME),
+            view: ResultsView::default(),
+            regions: Regions::default(),
+        }
+    }
+}
+
+impl VisualMemory {
+    fn reset(&mut self) {
+        let new = Self {
+            savestate: std::mem::take(&mut self.savestate),
+            clock: self.clock.clone(),
+            view: self.view,
+            ..Default::default()
+        };
+        *self = new;
+    }
+
+    /// Side length of the square grid for the current level.
+    fn side(&self) -> usize {
+        3 + self.level as usize / 3
+    }
+
+    fn play(&mut self) {
+        self.start = Some(self.clock.now());
+        self.show();
+    }
+
+    fn show(&mut self) {
+        self.populate();
+        self.mode = Mode::Watching(self.clock.now());
+    }
+
+    fn populate(&mut self) {
+        let cells = self.side() * self.side();
+        let amount = (self.level as usize + 2).min(cells - 1);
+
+        self.targets = vec![false; cells];
+        self.found = vec![false; cells];
+        self.missed = vec![false; cells];
+        self.cursor = self.cursor.min(cells - 1);
+
+        for i in sample(&mut self.rng, cells, amount) {
+            self.targets[i] = true;
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.found[index] || self.missed[index] {
+            return;
+        }
+
+        if self.targets[index] {
+            self.found[index] = true;
+            if self.found == self.targets {
+                self.mode = Mode::Pause(self.clock.now());
+            }
+        } else {
+            self.missed[index] = true;
+            self.lives -= 1;
+            if self.lives == 0 {
+                self.mode = Mode::Results;
+                self.savestate.update(
+                    self.level as f32,
+                    VARIANT,
+                    self.start
+                        .map(|s| self.clock.elapsed(s))
+                        .unwrap_or_default(),
+                    self.rng.seed(),
+                );
+            }
+        }
+    }
+
+    fn next_level(&mut self) {
+        self.level += 1;
+        self.lives = LIVES;
+        self.show();
+    }
+
+    fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let side = self.side() as isize;
+        let x = (self.cursor as isize % side + dx).clamp(0, side - 1);
+        let y = (self.cursor as isize / side + dy).clamp(0, side - 1);
+        self.cursor = (y * side + x) as usize;
+    }
+
+    fn key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('r') => self.reset(),
+            _ => (),
+        }
+
+        match self.mode {
+            Mode::Waiting => {
+                if let KeyCode::Enter | KeyCode::Char(' ') = key.code {
+                    self.play();
+                }
+            }
+            Mode::Playing => match key.code {
+                KeyCode::Left | KeyCode::Char('h') => self.move_cursor(-1, 0),
+                KeyCode::Right | KeyCode::Char('l') => self.move_cursor(1, 0),
+                KeyCode::Up | KeyCode::Char('k') => self.move_cursor(0, -1),
+                KeyCode::Down | KeyCode::Char('j') => self.move_cursor(0, 1),
+                KeyCode::Enter | KeyCode::Char(' ') => self.select(self.cursor),
+                _ => (),
+            },
+            Mode::Results => match key.code {
+                KeyCode::Enter => self.reset(),
+                KeyCode::Tab => self.view.tab.toggle(),
+                KeyCode::Char('h') => self.view.toggle_histogram(),
+                _ => (),
+            },
+            Mode::Watching(_) | Mode::Pause(_) => (),
+        }
+    }
+
+    fn mouse_event(&mut self, mouse: MouseEvent) {
+        if let MouseEventKind::Down(event::MouseButton::Left) = mouse.kind {
+        } else {
+            return;
+        }
+
+        match self.mode {
+            Mode::Waiting => self.play(),
+            Mode::Playing => {
+                if let Some(index) = self.regions.at(mouse.column, mouse.row) {
+                    self.cursor = index;
+                    self.select(index);
+                }
+            }
+            Mode::Results => self.reset(),
+            Mode::Watching(_) | Mode::Pause(_) => (),
+        }
+    }
+
+    /// Lays out the grid cells, numbered row by row.
+    fn layout(&self, area: Rect) -> Regions<usize> {
+        match self.mode {
+            Mode::Watching(_) | Mode::Playing | Mode::Pause(_) => (),
+            Mode::Waiting | Mode::Results => return Regions::default(),
+        }
+
+        let main = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area)[1]
+            .inner(Margin {
+                horizontal: 1,
+                vertical: 1,
+            });
+
+        let side = self.side() as u16;
+        let size = (main.height.saturating_sub(2) / side)
+            .min(main.width / (side * 2))
+            .min(MAX_CELL);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [Constraint::Min(0), Constraint::Length(2)]
+                    .into_iter()
+                    .chain((0..side).map(|_| Constraint::Length(size)))
+                    .chain([Constraint::Min(0)]),
+            )
+            .split(main);
+
+        rows[2..2 + side as usize]
+            .iter()
+            .flat_map(|row| {
+                Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(
+                        [Constraint::Min(0)]
+                            .into_iter()
+                            .chain((0..side).map(|_| Constraint::Length(size * 2)))
+                            .chain([Constraint::Min(0)]),
+                    )
+                    .split(*row)[1..=side as usize]
+                    .to_vec()
+            })
+            .enumerate()
+            .collect()
+    }
+}
+
+impl Game for VisualMemory {
+    fn run<B: Backend>(
+        terminal: &mut Terminal<B>,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<()> {
+        let mut game = Self::load().unwrap_or_default();
+        game.clock = events.clock();
+
+        while !game.exit {
+            terminal.draw(|frame| game.draw(frame))?;
+            if let Err(e) = game.handle_input(events) {
+                game.save();
+                return Err(e);
+            }
+        }
+
+        game.save();
+        Ok(())
+    }
+
+    fn handle_input(&mut self, events: &mut dyn EventSource) -> std::io::Result<()> {
+        let dur = match self.mode {
+            Mode::Watching(instant) => {
+                let dur = Duration::from_millis(FLASH).saturating_sub(self.clock.elapsed(instant));
+                if dur.is_zero() {
+                    self.mode = Mode::Playing;
+                    return Ok(());
+                }
+                dur
+            }
+            Mode::Pause(instant) => {
+                let dur = Duration::from_millis(PAUSE).saturating_sub(self.clock.elapsed(instant));
+                if dur.is_zero() {
+                    self.next_level();
+                    return Ok(());
+                }
+                dur
+            }
+            _ => Duration::MAX,
+        };
+
+        if events.poll(dur)? {
+            match events.read()? {
+                event::Event::Key(key) => self.key_event(key),
+                event::Event::Mouse(mouse) => self.mouse_event(mouse),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        self.regions = self.layout(frame.area());
+        frame.render_widget(&*self, frame.area());
+    }
+}
+
+impl Filed<'_> for VisualMemory {
+    type SaveState = SaveState;
+    const NAME: &'static str = FILE_NAME;
+
+    fn get_savestate(&self) -> Self::SaveState {
+        self.savestate.clone()
+    }
+
+    fn from_savestate(savestate: Self::SaveState) -> Self {
+        Self {
+            savestate,
+            ..Default::default()
+        }
+    }
+}
+
+impl Widget for &VisualMemory {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let vert = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        Paragraph::new(Span::from("Visual Memory Test").fg(Color::Red))
+            .centered()
+            .block(Block::bordered().border_set(border::DOUBLE))
+            .render(vert[0], buf);
+
+        let block = Block::bordered().border_set(border::DOUBLE);
+
+        let main = vert[1].inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+
+        match self.mode {
+            Mode::Waiting => {
+                block.title("╡ Menu ╞").render(vert[1], buf);
+
+                let layout = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Min(0),
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                        Constraint::Min(0),
+                    ])
+                    .split(main);
+
+                Paragraph::new("Click/Enter to start playing")
+                    .centered()
+                    .render(layout[1], buf);
+                Paragraph::new("Memorize the squares, then click them (arrows + Enter work too)")
+                    .set_style(Color::DarkGray)
+                    .italic()
+                    .centered()
+                    .render(layout[2], buf);
+            }
+            Mode::Watching(_) | Mode::Playing | Mode::Pause(_) => {
+                let title = match self.mode {
+                    Mode::Watching(_) => "╡ Watching ╞",
+                    _ => "╡ Playing ╞",
+                };
+                block.title(title).render(vert[1], buf);
+
+                let top = self.regions.get(0).unwrap_or(main).y;
+                let header = Rect {
+                    y: top.saturating_sub(2),
+                    height: 1,
+                    ..main
+                };
+
+                let stats = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([
+                        Constraint::Min(0),
+                        Constraint::Percentage(10),
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(10),
+                        Constraint::Min(0),
+                    ])
+                    .split(header);
+
+                Paragraph::new(format!("Level: {}", self.level))
+                    .centered()
+                    .render(stats[1], buf);
+                Paragraph::new(format!("Lives: {}", self.lives))
+                    .centered()
+                    .render(stats[3], buf);
+
+                for (i, cell) in self.regions.iter() {
+                    let shown = match self.mode {
+                        Mode::Playing => self.found[i],
+                        _ => self.targets[i],
+                    };
+
+                    if shown {
+                        Block::bordered()
+                            .border_set(border::QUADRANT_INSIDE)
+                            .set_style(Style::default().fg(Color::White))
+                            .render(cell, buf);
+                        Block::new()
+                            .set_style(Style::default().bg(Color::White))
+                            .render(
+                                cell.inner(Margin {
+                                    horizontal: 1,
+                                    vertical: 1,
+                                }),
+                                buf,
+                            );
+                    } else if self.missed[i] {
+                        Block::bordered()
+                            .border_set(border::THICK)
+                            .set_style(Style::default().bg(Color::DarkGray))
+                            .render(cell, buf);
+                    } else {
+                        Block::bordered()
+                            .border_set(border::THICK)
+                            .render(cell, buf);
+                    }
+
+                    // cursor
+                    if self.mode == Mode::Playing && i == self.cursor {
+                        Block::bordered()
+                            .border_set(border::THICK)
+                            .set_style(Color::LightRed)
+                            .render(cell, buf);
+                    }
+                }
+            }
+            Mode::Results => {
+                block.title("╡ Results ╞").render(vert[1], buf);
+
+                render_results(
+                    self.view,
+                    self.level as f64,
+                    &self.savestate,
+                    &POPULATION,
+                    main,
+                    buf,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend, layout::Rect};
+
+    use super::*;
+    use crate::app::events::ScriptedEvents;
+
+    /// Draws and handles input until the game has used up `events` or quit.
+    fn play(
+        game: &mut VisualMemory,
+        terminal: &mut Terminal<TestBackend>,
+        events: &mut ScriptedEvents,
+    ) {
+        while !game.exit && !events.is_empty() {
+            terminal.draw(|frame| game.draw(frame)).unwrap();
+            game.handle_input(events).unwrap();
+        }
+        terminal.draw(|frame| game.draw(frame)).unwrap();
+    }
+
+    /// Lets `millis` pass and has the game notice that its timer ran out.
+    fn wait(game: &mut VisualMemory, millis: u64) {
+        game.clock.advance(Duration::from_millis(millis));
+        game.handle_input(&mut ScriptedEvents::default()).unwrap();
+    }
+
+    fn center(rect: Rect) -> (u16, u16) {
+        (rect.x + rect.width / 2, rect.y + rect.height / 2)
+    }
+
+    /// Clicks on the first `count` cells that are (or aren't) targets.
+    fn click(game: &VisualMemory, targets: bool, count: usize) -> ScriptedEvents {
+        game.regions
+            .iter()
+            .filter(|&(i, _)| game.targets[i] == targets)
+            .take(count)
+            .fold(ScriptedEvents::default(), |events, (_, rect)| {
+                let (x, y) = center(rect);
+                events.click(x, y)
+            })
+    }
+
+    #[test]
+    fn levels() {
+        let mut game = VisualMemory {
+            clock: Clock::manual(),
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+        play(
+            &mut game,
+            &mut terminal,
+            &mut ScriptedEvents::default().key(KeyCode::Enter),
+        );
+        assert_eq!(game.regions.iter().count(), 9);
+        assert_eq!(game.targets.iter().filter(|&&t| t).count(), 3);
+        wait(&mut game, FLASH);
+        assert_eq!(game.mode, Mode::Playing);
+
+        let mut events = click(&game, true, usize::MAX);
+        play(&mut game, &mut terminal, &mut events);
+        assert!(matches!(game.mode, Mode::Pause(_)));
+        wait(&mut game, PAUSE);
+        assert_eq!(game.level, 2);
+        wait(&mut game, FLASH);
+
+        // A miss costs a life, which comes back with the next level.
+        let mut events = click(&game, false, 1);
+        play(&mut game, &mut terminal, &mut events);
+        assert_eq!(game.lives, LIVES - 1);
+        let mut events = click(&game, true, usize::MAX);
+        play(&mut game, &mut terminal, &mut events);
+        wait(&mut game, PAUSE);
+        assert_eq!(game.lives, LIVES);
+
+        // The grid grows every third level.
+        assert_eq!(game.level, 3);
+        terminal.draw(|frame| game.draw(frame)).unwrap();
+        assert_eq!(game.regions.iter().count(), 16);
+        wait(&mut game, FLASH);
+
+        let mut events = click(&game, false, LIVES as usize);
+        play(&mut game, &mut terminal, &mut events);
+        assert_eq!(game.mode, Mode::Results);
+        let attempt = game.savestate.attempts.last().unwrap();
+        assert_eq!(attempt.score, 3.0);
+        assert_eq!(attempt.duration_ms, 3 * FLASH + 2 * PAUSE);
+    }
+}
@@ -0,0 +1,11 @@
+use std::time::Instant;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Waiting,
+    Watching(Instant),
+    Playing,
+    Pause(Instant),
+    Results,
+}
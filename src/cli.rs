@@ -0,0 +1,623 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, Write},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+
+use crate::app::{
+    export::{self, Format},
+    import,
+    periods::{self, Period},
+    players,
+    profile::Profile,
+    reaction_time,
+    registry::{self, Entry},
+    savestate::SaveState,
+    storage,
+};
+
+const COMMANDS: &str = "\
+Usage: humanbenchmark [--player <name>] [--seed <n>] [--session-gap <minutes>] [command]
+
+Options:
+  --player <name>          play as this player instead of the one picked last
+  --seed <n>               generate the same targets, numbers and texts as anyone using <n>
+  --session-gap <minutes>  shortest break that starts a new session (default 30)
+
+Commands:
+  (none)                   open the menu
+  play <game>              start a test directly
+       [--trials <n>]      trials per session of reaction-time, kept for next time
+  stats [game] [--json]    print saved results without opening the TUI
+        [--by hour|weekday]  group them by hour of the day or day of the week
+  profile                  print your percentile in every test, by category and overall
+  players                  list the players on this machine
+  leaderboard [game]       rank the players by their average [--json]
+  export [--format csv|jsonl] [--game <game>] [--since <date>]
+                           print every attempt, one per row (csv by default)
+  import <file>            add the attempts of an export, or of a CSV of game,score,timestamp
+  reset <game> [--yes]     delete the save file of a test
+  help                     print this message";
+
+pub struct Args {
+    pub command: Command,
+    pub seed: Option<u64>,
+    pub session_gap: Option<Duration>,
+    pub player: Option<String>,
+}
+
+pub enum Command {
+    Menu,
+    Play {
+        game: &'static Entry,
+        /// Trials per session, for Reaction Time.
+        trials: Option<u32>,
+    },
+    Stats {
+        game: Option<&'static Entry>,
+        json: bool,
+        by: Option<Period>,
+    },
+    Profile,
+    Players,
+    Leaderboard {
+        game: Option<&'static Entry>,
+        json: bool,
+    },
+    Reset {
+        game: &'static Entry,
+        yes: bool,
+    },
+    Export {
+        format: Format,
+        game: Option<&'static Entry>,
+        since: Option<DateTime<Local>>,
+    },
+    Import(String),
+    Help,
+}
+
+pub fn usage() -> String {
+    let games: Vec<&str> = registry::GAMES.iter().map(|entry| entry.id).collect();
+    format!("{COMMANDS}\n\nGames:\n  {}", games.join(", "))
+}
+
+fn find(name: &str) -> Result<&'static Entry, String> {
+    registry::find(name).ok_or_else(|| format!("unknown game '{name}'"))
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut args: Vec<String> = args.into_iter().collect();
+
+    let seed = match take_value(&mut args, "--seed", "a number")? {
+        Some(value) => Some(
+            value
+                .parse()
+                .map_err(|_| format!("invalid seed '{value}'"))?,
+        ),
+        None => None,
+    };
+    let session_gap = match take_value(&mut args, "--session-gap", "a number of minutes")? {
+        Some(value) => Some(
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(|minutes| Duration::try_from_secs_f64(minutes * 60.0).ok())
+                .ok_or_else(|| format!("invalid session gap '{value}'"))?,
+        ),
+        None => None,
+    };
+    let player = take_value(&mut args, "--player", "a player's name")?;
+    let trials = match take_value(&mut args, "--trials", "a number")? {
+        Some(value) => Some(
+            value
+                .parse()
+                .ok()
+                .filter(|trials| (1..=reaction_time::MAX_TRIALS).contains(trials))
+                .ok_or_else(|| {
+                    format!(
+                        "invalid number of trials '{value}', expected 1 to {}",
+                        reaction_time::MAX_TRIALS
+                    )
+                })?,
+        ),
+        None => None,
+    };
+    let by = match take_value(&mut args, "--by", "'hour' or 'weekday'")? {
+        Some(value) => {
+            Some(Period::from_name(&value).ok_or_else(|| format!("cannot group by '{value}'"))?)
+        }
+        None => None,
+    };
+    let options = ExportOptions {
+        format: match take_value(&mut args, "--format", "'csv' or 'jsonl'")? {
+            Some(value) => {
+                Some(Format::from_name(&value).ok_or_else(|| format!("unknown format '{value}'"))?)
+            }
+            None => None,
+        },
+        game: match take_value(&mut args, "--game", "a game")? {
+            Some(name) => Some(find(&name)?),
+            None => None,
+        },
+        since: match take_value(&mut args, "--since", "a date")? {
+            Some(value) => Some(parse_date(&value)?),
+            None => None,
+        },
+    };
+
+    let command = parse_command(args, by, trials, options)?;
+    Ok(Args {
+        command,
+        seed,
+        session_gap,
+        player,
+    })
+}
+
+/// Removes an option that takes a value, as `--name value` or `--name=value`, and returns the
+/// last value given.
+fn take_value(args: &mut Vec<String>, name: &str, what: &str) -> Result<Option<String>, String> {
+    let mut value = None;
+    let prefix = format!("{name}=");
+    while let Some(i) = args
+        .iter()
+        .position(|arg| arg == name || arg.starts_with(&prefix))
+    {
+        value = Some(match args.remove(i).strip_prefix(name) {
+            Some("") if i < args.len() => args.remove(i),
+            Some(value) if value.starts_with('=') => value[1..].to_owned(),
+            _ => return Err(format!("'{name}' needs {what}")),
+        });
+    }
+    Ok(value)
+}
+
+/// Options that only `export` takes.
+struct ExportOptions {
+    format: Option<Format>,
+    game: Option<&'static Entry>,
+    since: Option<DateTime<Local>>,
+}
+
+/// A date as `YYYY-MM-DD`, meaning its start in local time, or a timestamp.
+fn parse_date(value: &str) -> Result<DateTime<Local>, String> {
+    import::parse_time(value).ok_or_else(|| format!("invalid date '{value}', expected YYYY-MM-DD"))
+}
+
+fn parse_command(
+    args: Vec<String>,
+    by: Option<Period>,
+    trials: Option<u32>,
+    options: ExportOptions,
+) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(Command::Menu);
+    };
+    let rest: Vec<String> = args.collect();
+    let (flags, positional): (Vec<&String>, Vec<&String>) =
+        rest.iter().partition(|arg| arg.starts_with('-'));
+
+    let game = |required: bool| -> Result<Option<&'static Entry>, String> {
+        match positional.as_slice() {
+            [] if required => Err(format!("'{command}' needs a game")),
+            [] => Ok(None),
+            [name] => find(name).map(Some),
+            _ => Err(format!("too many arguments for '{command}'")),
+        }
+    };
+    let flag = |names: &[&str]| flags.iter().any(|f| names.contains(&f.as_str()));
+    let known_flags = |names: &[&str]| match flags.iter().find(|f| !names.contains(&f.as_str())) {
+        Some(f) => Err(format!("unknown option '{f}'")),
+        None => Ok(()),
+    };
+
+    if trials.is_some() && command != "play" {
+        return Err(String::from(
+            "'--trials' only works with 'play reaction-time'",
+        ));
+    }
+    if by.is_some() && command != "stats" {
+        return Err(String::from("'--by' only works with 'stats'"));
+    }
+    let exporting = options.format.is_some() || options.game.is_some() || options.since.is_some();
+    if exporting && command != "export" {
+        return Err(String::from(
+            "'--format', '--game' and '--since' only work with 'export'",
+        ));
+    }
+
+    match command.as_str() {
+        "play" => {
+            known_flags(&[])?;
+            let game = game(true)?.unwrap();
+            if trials.is_some() && game.id != "reaction-time" {
+                return Err(String::from("'--trials' only works with reaction-time"));
+            }
+            Ok(Command::Play { game, trials })
+        }
+        "stats" => {
+            known_flags(&["--json"])?;
+            Ok(Command::Stats {
+                game: game(false)?,
+                json: flag(&["--json"]),
+                by,
+            })
+        }
+        "profile" => {
+            known_flags(&[])?;
+            game(false)?.map_or(Ok(Command::Profile), |_| {
+                Err(String::from("'profile' takes no game"))
+            })
+        }
+        "players" => {
+            known_flags(&[])?;
+            game(false)?.map_or(Ok(Command::Players), |_| {
+                Err(String::from("'players' takes no game"))
+            })
+        }
+        "leaderboard" => {
+            known_flags(&["--json"])?;
+            Ok(Command::Leaderboard {
+                game: game(false)?,
+                json: flag(&["--json"]),
+            })
+        }
+        "reset" => {
+            known_flags(&["--yes", "-y"])?;
+            Ok(Command::Reset {
+                game: game(true)?.unwrap(),
+                yes: flag(&["--yes", "-y"]),
+            })
+        }
+        "export" => {
+            known_flags(&[])?;
+            game(false)?.map_or(
+                Ok(Command::Export {
+                    format: options.format.unwrap_or(Format::Csv),
+                    game: options.game,
+                    since: options.since,
+                }),
+                |_| Err(String::from("'export' takes its game with '--game'")),
+            )
+        }
+        "import" => {
+            known_flags(&[])?;
+            match positional.as_slice() {
+                [file] => Ok(Command::Import(file.to_string())),
+                [] => Err(String::from("'import' needs a file")),
+                _ => Err(String::from("too many arguments for 'import'")),
+            }
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        _ => Err(format!("unknown command '{command}'")),
+    }
+}
+
+/// Save state of `game`, warning about a save file that can't be read instead of passing it off
+/// as empty, and falling back to its newest good backup.
+fn load(game: &Entry) -> SaveState {
+    match (game.read)() {
+        Ok(savestate) => savestate.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("warning: the save file of {} can't be read: {e}", game.name);
+            match (game.backup)() {
+                Some((backup, savestate)) => {
+                    eprintln!("warning: showing its newest good backup, {backup}");
+                    savestate
+                }
+                None => SaveState::default(),
+            }
+        }
+    }
+}
+
+pub fn stats(game: Option<&'static Entry>, json: bool, by: Option<Period>) -> io::Result<()> {
+    let games: Vec<&Entry> = match game {
+        Some(game) => vec![game],
+        None => registry::GAMES.iter().collect(),
+    };
+    if let Some(period) = by {
+        return stats_by(&games, json, period);
+    }
+
+    if json {
+        let map: serde_json::Map<String, serde_json::Value> = games
+            .iter()
+            .map(|game| {
+                let savestate = load(game);
+                let mut value = serde_json::to_value(&savestate).unwrap_or(serde_json::Value::Null);
+                if let serde_json::Value::Object(fields) = &mut value {
+                    fields.insert(
+                        String::from("better"),
+                        serde_json::to_value(game.better).unwrap_or_default(),
+                    );
+                    fields.insert(
+                        String::from("stats"),
+                        serde_json::to_value(savestate.stats(game.better)).unwrap_or_default(),
+                    );
+                }
+                (game.name.to_owned(), value)
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&map)?);
+        return Ok(());
+    }
+
+    let mut table = format!(
+        "{:<16} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10}  {}\n",
+        "Game", "Attempts", "Average", "Best", "Median", "Std. dev.", "Last", "Last played"
+    );
+    for game in games {
+        let savestate = load(game);
+        let (last, played) = match savestate.attempts.last() {
+            Some(attempt) => (
+                format!("{:.1}", attempt.score),
+                attempt.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+            ),
+            None => (String::from("-"), String::from("-")),
+        };
+        let [best, median, std_dev] = match savestate.stats(game.better) {
+            Some(stats) => [stats.best, stats.median, stats.std_dev].map(|v| format!("{v:.1}")),
+            None => [(); 3].map(|_| String::from("-")),
+        };
+        let _ = writeln!(
+            table,
+            "{:<16} {:>8} {:>10.1} {:>10} {:>10} {:>10} {:>10}  {}",
+            game.name,
+            savestate.num_entries(),
+            savestate.avg_score(),
+            best,
+            median,
+            std_dev,
+            last,
+            played
+        );
+    }
+    print!("{table}");
+    Ok(())
+}
+
+/// Width of the longest bar of `stats --by`.
+const BAR_WIDTH: f32 = 30.0;
+
+fn stats_by(games: &[&Entry], json: bool, period: Period) -> io::Result<()> {
+    if json {
+        let map: serde_json::Map<String, serde_json::Value> = games
+            .iter()
+            .map(|game| {
+                let savestate = load(game);
+                let buckets = periods::buckets(&savestate.attempts, period);
+                (
+                    game.name.to_owned(),
+                    serde_json::to_value(buckets).unwrap_or_default(),
+                )
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&map)?);
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    for game in games {
+        let savestate = load(game);
+        let _ = writeln!(out, "{} by {}", game.name, period.name());
+        if savestate.attempts.is_empty() {
+            let _ = writeln!(out, "  no attempts yet\n");
+            continue;
+        }
+
+        let buckets = periods::buckets(&savestate.attempts, period);
+        let played = || buckets.iter().filter(|b| b.count > 0);
+        let max = played().map(|b| b.mean).fold(0.0, f32::max);
+        let best = played()
+            .map(|b| b.mean)
+            .reduce(|a, b| if game.better.is_better(b, a) { b } else { a });
+
+        let _ = writeln!(out, "  {:<7} {:>6} {:>10}", "", "Count", "Mean");
+        for bucket in &buckets {
+            if bucket.count == 0 {
+                let _ = writeln!(out, "  {:<7} {:>6} {:>10}", bucket.label, 0, "-");
+                continue;
+            }
+            let bar = "█".repeat((bucket.mean / max * BAR_WIDTH).round() as usize);
+            let mark = if Some(bucket.mean) == best {
+                "  best"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "  {:<7} {:>6} {:>10.1}  {bar}{mark}",
+                bucket.label, bucket.count, bucket.mean
+            );
+        }
+        out.push('\n');
+    }
+    print!("{out}");
+    Ok(())
+}
+
+pub fn profile() {
+    print!("{}", Profile::load().summary());
+}
+
+pub fn players() {
+    let active = players::active();
+    for player in players::list() {
+        let mark = if player == active { "*" } else { " " };
+        println!("{mark} {player}");
+    }
+}
+
+pub fn leaderboard(game: Option<&'static Entry>, json: bool) -> io::Result<()> {
+    let games: Vec<&Entry> = match game {
+        Some(game) => vec![game],
+        None => registry::GAMES.iter().collect(),
+    };
+
+    if json {
+        let map: serde_json::Map<String, serde_json::Value> = games
+            .iter()
+            .map(|game| {
+                let standings = players::leaderboard(game);
+                (
+                    game.name.to_owned(),
+                    serde_json::to_value(standings).unwrap_or_default(),
+                )
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&map)?);
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    for game in games {
+        let _ = writeln!(out, "{}", game.name);
+        let standings = players::leaderboard(game);
+        if standings.is_empty() {
+            let _ = writeln!(out, "  nobody has played this yet\n");
+            continue;
+        }
+
+        let _ = writeln!(
+            out,
+            "  {:<4} {:<24} {:>8} {:>10} {:>10} {:>10}",
+            "", "Player", "Attempts", "Average", "Best", "Percentile"
+        );
+        for (i, standing) in standings.iter().enumerate() {
+            let best = standing
+                .best
+                .map_or_else(|| String::from("-"), |best| format!("{best:.1}"));
+            let _ = writeln!(
+                out,
+                "  {:<4} {:<24} {:>8} {:>10.1} {:>10} {:>9.0}%",
+                format!("{}.", i + 1),
+                standing.player,
+                standing.attempts,
+                standing.average,
+                best,
+                standing.percentile
+            );
+        }
+        out.push('\n');
+    }
+    print!("{out}");
+    Ok(())
+}
+
+pub fn export(
+    format: Format,
+    game: Option<&'static Entry>,
+    since: Option<DateTime<Local>>,
+) -> io::Result<()> {
+    let games: Vec<(&Entry, SaveState)> = match game {
+        Some(game) => vec![(game, load(game))],
+        None => registry::GAMES
+            .iter()
+            .map(|game| (game, load(game)))
+            .collect(),
+    };
+    let records = export::records(&games, since);
+    io::stdout()
+        .lock()
+        .write_all(export::write(&records, format).as_bytes())
+}
+
+/// Most skipped or flagged rows listed after an import, so that a file in the wrong format
+/// doesn't bury the summary.
+const MAX_SKIPPED: usize = 20;
+
+pub fn import(file: &str) -> io::Result<()> {
+    let text = std::fs::read_to_string(file)?;
+    let report = import::import(&text);
+
+    for (line, reason) in report.skipped.iter().take(MAX_SKIPPED) {
+        eprintln!("skipped line {line}: {reason}");
+    }
+    if report.skipped.len() > MAX_SKIPPED {
+        eprintln!("skipped {} more lines", report.skipped.len() - MAX_SKIPPED);
+    }
+    for (line, flag) in report.flagged.iter().take(MAX_SKIPPED) {
+        eprintln!("line {line} doesn't count: {}", flag.reason());
+    }
+    if report.flagged.len() > MAX_SKIPPED {
+        eprintln!(
+            "{} more lines don't count",
+            report.flagged.len() - MAX_SKIPPED
+        );
+    }
+    for (game, imported) in registry::GAMES.iter().zip(&report.imported) {
+        if *imported > 0 {
+            println!("{:<16} {imported:>6}", game.name);
+        }
+    }
+    println!(
+        "Imported {} attempts for {} ({} flagged), skipped {} rows.",
+        report.total(),
+        players::active(),
+        report.flagged.len(),
+        report.skipped.len()
+    );
+    Ok(())
+}
+
+pub fn reset(game: &Entry, yes: bool) -> io::Result<()> {
+    let Some(file) = (game.save_file)() else {
+        return Err(io::Error::other("could not find the data directory"));
+    };
+    if !storage::exists(&file) {
+        println!("{} has no saved results.", game.name);
+        return Ok(());
+    }
+
+    if !yes {
+        print!(
+            "Delete all saved results for {} ({file} and its backups)? [y/N] ",
+            game.name
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Nothing was deleted.");
+            return Ok(());
+        }
+    }
+
+    storage::remove(&file)?;
+    println!("Deleted {file} and its backups.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn options_take_their_value_either_way() {
+        let mut rest = args(&["--seed=5", "stats", "--seed", "7"]);
+        assert_eq!(
+            take_value(&mut rest, "--seed", "a number"),
+            Ok(Some("7".to_owned()))
+        );
+        assert_eq!(rest, ["stats"]);
+
+        let mut rest = args(&["stats", "--seed"]);
+        assert!(take_value(&mut rest, "--seed", "a number").is_err());
+    }
+
+    #[test]
+    fn options_only_match_their_whole_name() {
+        let mut rest = args(&["--seedling", "--seed-7"]);
+        assert_eq!(take_value(&mut rest, "--seed", "a number"), Ok(None));
+        assert_eq!(rest, ["--seedling", "--seed-7"]);
+    }
+}
@@ -1,30 +1,84 @@
 mod app;
-use crate::app::Game;
+mod cli;
+mod terminal;
 
-use std::io::{self, stdout};
+use std::io;
 
-use app::Menu;
-use ratatui::crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode},
-};
+use app::{Menu, events::TerminalEvents};
+use cli::Command;
+use ratatui::DefaultTerminal;
+use terminal::TerminalGuard;
 
 fn main() -> io::Result<()> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnableMouseCapture)?;
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{}", cli::usage());
+            std::process::exit(2);
+        }
+    };
 
-    let mut terminal = ratatui::init(); // Your init method
+    if let Err(e) = app::players::migrate() {
+        eprintln!("warning: could not move the save files into the default player's: {e}");
+    }
+    if let Some(player) = &args.player {
+        if !app::players::exists(player) {
+            eprintln!(
+                "error: there is no player called '{player}'\n\n{}",
+                cli::usage()
+            );
+            std::process::exit(2);
+        }
+        app::players::set_active(player);
+    }
+    if let Some(seed) = args.seed {
+        app::rng::set_seed(seed);
+    }
+    if let Some(gap) = args.session_gap {
+        app::sessions::set_gap(gap);
+    }
 
-    // Run the app
-    let app_result = Menu::run(&mut terminal);
+    match args.command {
+        Command::Menu => tui(|terminal| Menu::run(terminal, &mut TerminalEvents::default())),
+        Command::Play { game, trials } => {
+            if let Some(trials) = trials {
+                app::reaction_time::set_trials(trials);
+            }
+            tui(|terminal| app::play(game, terminal, &mut TerminalEvents::default()))
+        }
+        Command::Stats { game, json, by } => cli::stats(game, json, by),
+        Command::Profile => {
+            cli::profile();
+            Ok(())
+        }
+        Command::Players => {
+            cli::players();
+            Ok(())
+        }
+        Command::Leaderboard { game, json } => cli::leaderboard(game, json),
+        Command::Reset { game, yes } => cli::reset(game, yes),
+        Command::Export {
+            format,
+            game,
+            since,
+        } => cli::export(format, game, since),
+        Command::Import(file) => cli::import(&file),
+        Command::Help => {
+            println!("{}", cli::usage());
+            Ok(())
+        }
+    }
+}
+
+fn tui(app: impl FnOnce(&mut DefaultTerminal) -> io::Result<()>) -> io::Result<()> {
+    let mut terminal = TerminalGuard::new()?;
+    let app_result = app(&mut terminal);
+    drop(terminal);
 
-    // Restore terminal settings
-    execute!(stdout, DisableMouseCapture)?;
-    disable_raw_mode()?;
-    ratatui::restore(); // Your restore method
+    // Games have already saved on the way out, all that's left is the usual exit status.
+    if let Some(signal) = terminal::signal() {
+        std::process::exit(128 + signal);
+    }
 
     app_result
 }
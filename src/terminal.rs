@@ -0,0 +1,138 @@
+use std::{
+    io::{self, stdout},
+    ops::{Deref, DerefMut},
+    panic,
+    sync::{
+        Arc, Once, OnceLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use ratatui::{
+    DefaultTerminal, Terminal,
+    crossterm::{
+        cursor::Show,
+        event::{
+            DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
+            PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        },
+        execute,
+        terminal::{
+            EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+            supports_keyboard_enhancement,
+        },
+    },
+    prelude::CrosstermBackend,
+};
+#[cfg(unix)]
+use signal_hook::consts::SIGHUP;
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+static SIGNAL: OnceLock<Arc<AtomicUsize>> = OnceLock::new();
+static PANIC_HOOK: Once = Once::new();
+/// Whether the terminal was asked to report key repeats and releases, and has to be told to stop.
+static KEY_EVENT_TYPES: AtomicBool = AtomicBool::new(false);
+
+/// How often a blocking wait for input checks for termination signals.
+pub const SIGNAL_CHECK: Duration = Duration::from_millis(100);
+
+/// Owns the terminal while the TUI runs: raw mode, the alternate screen and mouse capture are
+/// turned on when it's created and turned off again when it's dropped, including when unwinding
+/// from a panic or returning early with an error.
+pub struct TerminalGuard {
+    terminal: DefaultTerminal,
+}
+
+impl TerminalGuard {
+    pub fn new() -> io::Result<Self> {
+        install_panic_hook();
+        install_signal_handlers()?;
+
+        enable_raw_mode()?;
+        // Until the guard exists, nothing else turns raw mode off again.
+        let terminal = Terminal::new(CrosstermBackend::new(stdout())).inspect_err(|_| {
+            let _ = restore();
+        })?;
+        let guard = Self { terminal };
+        execute!(stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        // Without this, a held key is indistinguishable from presses and never reported as one.
+        if supports_keyboard_enhancement().unwrap_or(false) {
+            execute!(
+                stdout(),
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+            KEY_EVENT_TYPES.store(true, Ordering::SeqCst);
+        }
+        Ok(guard)
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = DefaultTerminal;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+/// Puts the terminal back the way the shell expects it. Safe to call more than once.
+pub fn restore() -> io::Result<()> {
+    if KEY_EVENT_TYPES.swap(false, Ordering::SeqCst) {
+        let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+    }
+    let result = execute!(stdout(), DisableMouseCapture, LeaveAlternateScreen, Show);
+    disable_raw_mode()?;
+    result
+}
+
+/// Restores the terminal before the panic message gets printed, so that it's readable and the
+/// shell isn't left in raw mode.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            hook(info);
+        }));
+    });
+}
+
+fn install_signal_handlers() -> io::Result<()> {
+    if SIGNAL.get().is_some() {
+        return Ok(());
+    }
+
+    let flag = Arc::<AtomicUsize>::default();
+    // There's no SIGHUP on Windows, where closing the console ends the process anyway.
+    #[cfg(unix)]
+    let signals = [SIGTERM, SIGHUP, SIGINT];
+    #[cfg(not(unix))]
+    let signals = [SIGTERM, SIGINT];
+    for signal in signals {
+        signal_hook::flag::register_usize(signal, flag.clone(), signal as usize)?;
+    }
+    let _ = SIGNAL.set(flag);
+    Ok(())
+}
+
+/// Termination signal that was received, if any. Event sources check this while waiting, so
+/// that games can save and unwind instead of being killed mid-frame.
+pub fn signal() -> Option<i32> {
+    match SIGNAL.get()?.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal as i32),
+    }
+}